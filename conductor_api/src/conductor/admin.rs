@@ -75,7 +75,7 @@ impl ConductorAdmin for Conductor {
                 HolochainError::ConfigError(format!(
                     "Could not load DNA file \"{}\", Error: {}",
                     path_string,
-                    e.to_string()
+                    e.message()
                 ))
             })?;
 
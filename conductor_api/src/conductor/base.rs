@@ -399,9 +399,9 @@ impl Conductor {
 
                 // Storage:
                 if let StorageConfiguration::File { path } = instance_config.storage {
-                    context_builder = context_builder.with_file_storage(path).map_err(|hc_err| {
-                        format!("Error creating context: {}", hc_err.to_string())
-                    })?
+                    context_builder = context_builder
+                        .with_file_storage(path)
+                        .map_err(|hc_err| format!("Error creating context: {}", hc_err.message()))?
                 };
 
                 if config.logger.logger_type == "debug" {
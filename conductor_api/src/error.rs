@@ -29,7 +29,9 @@ impl fmt::Display for HolochainInstanceError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         let prefix = "Holochain Instance Error";
         match self {
-            HolochainInstanceError::InternalFailure(ref err) => write!(f, "{}: {}", prefix, err),
+            HolochainInstanceError::InternalFailure(ref err) => {
+                write!(f, "{}: {}", prefix, err.message())
+            }
             HolochainInstanceError::InstanceNotActiveYet => {
                 write!(f, "{}: Holochain instance is not active yet.", prefix)
             }
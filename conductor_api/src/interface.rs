@@ -1,5 +1,8 @@
+use error::HolochainInstanceError;
 use holochain_core::state::State;
-use holochain_core_types::{cas::content::Address, dna::capabilities::CapabilityCall};
+use holochain_core_types::{
+    cas::content::Address, dna::capabilities::CapabilityCall, error::HolochainError,
+};
 use Holochain;
 
 use jsonrpc_ws_server::jsonrpc_core::{self, types::params::Params, IoHandler, Value};
@@ -25,16 +28,41 @@ pub trait DispatchRpc {
     fn handler(self) -> IoHandler;
 }
 
+/// the message of an error as it is sent to JSON-RPC clients
+/// HolochainError goes without the [Variant] prefix of its Display, @see HolochainError::message
+trait RpcErrorMessage {
+    fn rpc_message(&self) -> String;
+}
+
+impl RpcErrorMessage for HolochainError {
+    fn rpc_message(&self) -> String {
+        self.message()
+    }
+}
+
+impl RpcErrorMessage for HolochainInstanceError {
+    fn rpc_message(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl RpcErrorMessage for String {
+    fn rpc_message(&self) -> String {
+        self.clone()
+    }
+}
+
+fn internal_error<E: RpcErrorMessage>(e: E) -> jsonrpc_core::Error {
+    let mut new = jsonrpc_core::Error::internal_error();
+    new.message = e.rpc_message();
+    new
+}
+
 macro_rules! conductor_call {
     ( |$conductor:ident| $call_expr:expr ) => {
         match * CONDUCTOR.lock().unwrap() {
             Some( ref mut $conductor) => {
-                $call_expr
-                    .map_err( | e | {
-                        let mut new = jsonrpc_core::Error::internal_error();
-                        new.message = e.to_string();
-                        new
-                    })
+                $call_expr.map_err(internal_error)
             }
             None => {
                 println!("Admin conductor function called without a conductor mounted as singleton!");
@@ -170,7 +198,9 @@ impl ConductorApiBuilder {
                                     &func_name,
                                     &params_string,
                                 )
-                                .map_err(|e| jsonrpc_core::Error::invalid_params(e.to_string()))?;
+                                .map_err(|e| {
+                                    jsonrpc_core::Error::invalid_params(e.rpc_message())
+                                })?;
                             Ok(Value::String(response.to_string()))
                         })
                     }
@@ -829,4 +859,26 @@ pub mod tests {
             r#"[{"id":"test-instance-1","dna":"bridge-callee","agent":"test-agent-1"}]"#
         );
     }
+
+    #[test]
+    fn internal_error_message_test() {
+        for (error, message) in vec![
+            (
+                internal_error(HolochainError::ConfigError(String::from("foo"))),
+                "foo",
+            ),
+            (
+                internal_error(HolochainInstanceError::InternalFailure(
+                    HolochainError::DnaMissing,
+                )),
+                "Holochain Instance Error: DNA is missing",
+            ),
+            (internal_error(String::from("bar")), "bar"),
+        ] {
+            let body = serde_json::to_value(&error).expect("jsonrpc error is valid JSON");
+            assert_eq!(json!(-32603), body["code"]);
+            // no [Variant] prefix of HolochainError Display is sent to clients
+            assert_eq!(json!(message), body["message"]);
+        }
+    }
 }
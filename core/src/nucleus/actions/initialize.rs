@@ -54,7 +54,7 @@ pub async fn initialize_application(
         context_clone
             .action_channel()
             .send(ActionWrapper::new(Action::ReturnInitializationResult(
-                Some(dna_commit.map_err(|e| e.to_string()).err().unwrap()),
+                Some(dna_commit.map_err(|e| e.message()).err().unwrap()),
             )))
             .expect("Action channel not usable in initialize_application()");
         return Err(HolochainError::new("error committing DNA"));
@@ -73,7 +73,7 @@ pub async fn initialize_application(
         context_clone
             .action_channel()
             .send(ActionWrapper::new(Action::ReturnInitializationResult(
-                Some(agent_id_commit.map_err(|e| e.to_string()).err().unwrap()),
+                Some(agent_id_commit.map_err(|e| e.message()).err().unwrap()),
             )))
             .expect("Action channel not usable in initialize_application()");
         return Err(HolochainError::new("error committing Agent"));
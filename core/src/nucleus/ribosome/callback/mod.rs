@@ -154,7 +154,7 @@ impl From<RibosomeEncodedValue> for CallbackResult {
     fn from(ribosome_return_code: RibosomeEncodedValue) -> CallbackResult {
         match ribosome_return_code {
             RibosomeEncodedValue::Failure(ribosome_error_code) => {
-                CallbackResult::Fail(ribosome_error_code.to_string())
+                CallbackResult::Fail(ribosome_error_code.as_str().to_string())
             }
             RibosomeEncodedValue::Allocation(ribosome_allocation) => {
                 match WasmAllocation::try_from(ribosome_allocation) {
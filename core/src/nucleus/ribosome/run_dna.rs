@@ -148,7 +148,7 @@ pub fn run_dna(wasm: Vec<u8>, parameters: Option<Vec<u8>>, data: WasmCallData) -
         }
 
         RibosomeEncodedValue::Failure(err_code) => {
-            return_log_msg = return_code.to_string();
+            return_log_msg = err_code.localized();
             return_result = Err(HolochainError::RibosomeFailed(format!(
                 "Zome function failure: {}",
                 err_code.as_str()
@@ -165,11 +165,12 @@ pub fn run_dna(wasm: Vec<u8>, parameters: Option<Vec<u8>>, data: WasmCallData) -
                             return_result = Ok(JsonString::from(json_string));
                         }
                         Err(err) => {
-                            return_log_msg = err.to_string();
-                            return_result = Err(HolochainError::RibosomeFailed(format!(
+                            let error = HolochainError::RibosomeFailed(format!(
                                 "WASM failed to return value: {}",
                                 err
-                            )));
+                            ));
+                            return_log_msg = error.message();
+                            return_result = Err(error);
                         }
                     }
                 }
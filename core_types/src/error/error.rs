@@ -1,6 +1,6 @@
 use self::HolochainError::*;
 use crate::{
-    error::{DnaError, RibosomeErrorCode},
+    error::{DnaError, RibosomeEncodedValue, RibosomeErrorCode, ValidationError},
     json::*,
 };
 use futures::channel::oneshot::Canceled as FutureCanceled;
@@ -89,10 +89,12 @@ pub enum HolochainError {
     InvalidOperationOnSysEntry,
     CapabilityCheckFailed,
     ValidationFailed(String),
+    Validation(ValidationError),
     Ribosome(RibosomeErrorCode),
     RibosomeFailed(String),
     ConfigError(String),
     Timeout,
+    MultipleErrors(Vec<HolochainError>),
 }
 
 pub type HcResult<T> = Result<T, HolochainError>;
@@ -101,46 +103,161 @@ impl HolochainError {
     pub fn new(msg: &str) -> HolochainError {
         HolochainError::ErrorGeneric(msg.to_string())
     }
-}
 
-impl fmt::Display for HolochainError {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    /// "Expected {expected} but got {got}" as an ErrorGeneric
+    /// TODO give this a dedicated ExpectedGot variant once callers have moved over
+    pub fn expected_got(expected: impl fmt::Display, got: impl fmt::Display) -> HolochainError {
+        HolochainError::ErrorGeneric(format!("Expected {} but got {}", expected, got))
+    }
+
+    /// all the values if every result is Ok, otherwise every error rather than just the first
+    /// a single error is returned as is, more than one are wrapped in MultipleErrors
+    /// e.g. for batch commits where each failure should be reported
+    pub fn collect_errors<T, I>(results: I) -> Result<Vec<T>, HolochainError>
+    where
+        I: IntoIterator<Item = Result<T, HolochainError>>,
+    {
+        let mut values = Vec::new();
+        let mut errors = Vec::new();
+        for result in results {
+            match result {
+                Ok(value) => values.push(value),
+                Err(error) => errors.push(error),
+            }
+        }
+        match errors.len() {
+            0 => Ok(values),
+            1 => Err(errors.remove(0)),
+            _ => Err(HolochainError::MultipleErrors(errors)),
+        }
+    }
+
+    /// the message without the variant prefix that Display adds
+    /// e.g. for payloads that already say where the error came from, like ZomeApiError::Internal
+    pub fn message(&self) -> String {
         match self {
-            ErrorGeneric(err_msg) => write!(f, "{}", err_msg),
-            NotImplemented(description) => write!(f, "not implemented: {}", description),
-            LoggingError => write!(f, "logging failed"),
-            DnaMissing => write!(f, "DNA is missing"),
-            Dna(dna_err) => write!(f, "{}", dna_err),
-            IoError(err_msg) => write!(f, "{}", err_msg),
-            SerializationError(err_msg) => write!(f, "{}", err_msg),
+            ErrorGeneric(err_msg) => err_msg.to_owned(),
+            NotImplemented(description) => format!("not implemented: {}", description),
+            LoggingError => "logging failed".to_string(),
+            DnaMissing => "DNA is missing".to_string(),
+            Dna(dna_err) => dna_err.to_string(),
+            IoError(err_msg) => err_msg.to_owned(),
+            SerializationError(err_msg) => err_msg.to_owned(),
             InvalidOperationOnSysEntry => {
-                write!(f, "operation cannot be done on a system entry type")
+                "operation cannot be done on a system entry type".to_string()
             }
-            CapabilityCheckFailed => write!(f, "Caller does not have Capability to make that call"),
-            ValidationFailed(fail_msg) => write!(f, "{}", fail_msg),
-            Ribosome(err_code) => write!(f, "{}", err_code.as_str()),
-            RibosomeFailed(fail_msg) => write!(f, "{}", fail_msg),
-            ConfigError(err_msg) => write!(f, "{}", err_msg),
-            Timeout => write!(f, "timeout"),
+            CapabilityCheckFailed => {
+                "Caller does not have Capability to make that call".to_string()
+            }
+            ValidationFailed(fail_msg) => fail_msg.to_owned(),
+            Validation(validation_error) => validation_error.to_string(),
+            Ribosome(err_code) => err_code.as_str().to_string(),
+            RibosomeFailed(fail_msg) => fail_msg.to_owned(),
+            ConfigError(err_msg) => err_msg.to_owned(),
+            Timeout => "timeout".to_string(),
+            MultipleErrors(errors) => errors
+                .iter()
+                .map(|error| error.to_string())
+                .collect::<Vec<String>>()
+                .join("; "),
+        }
+    }
+
+    /// the prefix Display puts before message()
+    fn variant_name(&self) -> &'static str {
+        match self {
+            ErrorGeneric(_) => "ErrorGeneric",
+            NotImplemented(_) => "NotImplemented",
+            LoggingError => "LoggingError",
+            DnaMissing => "DnaMissing",
+            Dna(_) => "Dna",
+            IoError(_) => "IoError",
+            SerializationError(_) => "SerializationError",
+            InvalidOperationOnSysEntry => "InvalidOperationOnSysEntry",
+            CapabilityCheckFailed => "CapabilityCheckFailed",
+            ValidationFailed(_) => "ValidationFailed",
+            Validation(_) => "Validation",
+            Ribosome(_) => "Ribosome",
+            RibosomeFailed(_) => "RibosomeFailed",
+            ConfigError(_) => "ConfigError",
+            Timeout => "Timeout",
+            MultipleErrors(_) => "MultipleErrors",
+        }
+    }
+
+    pub fn is_serialization_error(&self) -> bool {
+        match self {
+            SerializationError(_) => true,
+            _ => false,
+        }
+    }
+
+    pub fn is_io_error(&self) -> bool {
+        match self {
+            IoError(_) => true,
+            _ => false,
+        }
+    }
+
+    /// both a ribosome error code and a failure message from the ribosome
+    pub fn is_ribosome_error(&self) -> bool {
+        match self {
+            Ribosome(_) | RibosomeFailed(_) => true,
+            _ => false,
+        }
+    }
+
+    pub fn is_timeout(&self) -> bool {
+        match self {
+            Timeout => true,
+            _ => false,
+        }
+    }
+
+    /// the Failure to return to the wasm guest for this error
+    /// @see From<HolochainError> for RibosomeErrorCode
+    /// e.g. result.unwrap_or_else(|e| e.into_ribosome_return()) at host function exits
+    pub fn into_ribosome_return(self) -> RibosomeEncodedValue {
+        RibosomeEncodedValue::Failure(RibosomeErrorCode::from(self))
+    }
+
+    /// the wrapped errors of MultipleErrors, None for any other error
+    pub fn errors(&self) -> Option<&[HolochainError]> {
+        match self {
+            HolochainError::MultipleErrors(errors) => Some(errors),
+            _ => None,
         }
     }
 }
 
+impl fmt::Display for HolochainError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "[{}] {}", self.variant_name(), self.message())
+    }
+}
+
 impl Error for HolochainError {}
 
+/// message() without the variant prefix as the string is usually wrapped again or sent on
 impl From<HolochainError> for String {
     fn from(holochain_error: HolochainError) -> Self {
-        holochain_error.to_string()
+        holochain_error.message()
     }
 }
 
 impl From<String> for HolochainError {
     fn from(error: String) -> Self {
-        HolochainError::new(&error)
+        HolochainError::ErrorGeneric(error)
     }
 }
 
-impl From<&'static str> for HolochainError {
+impl From<ValidationError> for HolochainError {
+    fn from(validation_error: ValidationError) -> Self {
+        HolochainError::Validation(validation_error)
+    }
+}
+
+impl<'a> From<&'a str> for HolochainError {
     fn from(error: &str) -> Self {
         HolochainError::new(error)
     }
@@ -234,7 +351,7 @@ mod tests {
     /// test that we can convert an error to a string
     fn to_string() {
         let err = HolochainError::new("foo");
-        assert_eq!("foo", err.to_string());
+        assert_eq!("[ErrorGeneric] foo", err.to_string());
     }
 
     #[test]
@@ -278,45 +395,222 @@ mod tests {
     /// show Error implementation for HolochainError
     fn error_test() {
         for (input, output) in vec![
-            (HolochainError::ErrorGeneric(String::from("foo")), "foo"),
+            (
+                HolochainError::ErrorGeneric(String::from("foo")),
+                "[ErrorGeneric] foo",
+            ),
             (
                 HolochainError::NotImplemented("reason".into()),
-                "not implemented: reason",
+                "[NotImplemented] not implemented: reason",
+            ),
+            (
+                HolochainError::LoggingError,
+                "[LoggingError] logging failed",
+            ),
+            (HolochainError::DnaMissing, "[DnaMissing] DNA is missing"),
+            (
+                HolochainError::ConfigError(String::from("foo")),
+                "[ConfigError] foo",
             ),
-            (HolochainError::LoggingError, "logging failed"),
-            (HolochainError::DnaMissing, "DNA is missing"),
-            (HolochainError::ConfigError(String::from("foo")), "foo"),
             (
                 HolochainError::Dna(DnaError::ZomeNotFound(String::from("foo"))),
-                "foo",
+                "[Dna] foo",
             ),
             (
                 HolochainError::Dna(DnaError::TraitNotFound(String::from("foo"))),
-                "foo",
+                "[Dna] foo",
             ),
             (
                 HolochainError::Dna(DnaError::ZomeFunctionNotFound(String::from("foo"))),
-                "foo",
+                "[Dna] foo",
+            ),
+            (
+                HolochainError::IoError(String::from("foo")),
+                "[IoError] foo",
             ),
-            (HolochainError::IoError(String::from("foo")), "foo"),
             (
                 HolochainError::SerializationError(String::from("foo")),
-                "foo",
+                "[SerializationError] foo",
             ),
             (
                 HolochainError::InvalidOperationOnSysEntry,
-                "operation cannot be done on a system entry type",
+                "[InvalidOperationOnSysEntry] operation cannot be done on a system entry type",
             ),
             (
                 HolochainError::CapabilityCheckFailed,
-                "Caller does not have Capability to make that call",
+                "[CapabilityCheckFailed] Caller does not have Capability to make that call",
+            ),
+            (
+                HolochainError::ValidationFailed(String::from("foo")),
+                "[ValidationFailed] foo",
+            ),
+            (
+                HolochainError::Ribosome(RibosomeErrorCode::CallbackFailed),
+                "[Ribosome] Callback failed",
+            ),
+            (
+                HolochainError::RibosomeFailed(String::from("foo")),
+                "[RibosomeFailed] foo",
+            ),
+            (HolochainError::Timeout, "[Timeout] timeout"),
+            (
+                HolochainError::MultipleErrors(vec![
+                    HolochainError::Timeout,
+                    HolochainError::ErrorGeneric(String::from("foo")),
+                ]),
+                "[MultipleErrors] [Timeout] timeout; [ErrorGeneric] foo",
+            ),
+            (
+                HolochainError::Validation(ValidationError::from(("foo", "bar"))),
+                "[Validation] foo: bar",
             ),
-            (HolochainError::Timeout, "timeout"),
         ] {
             assert_eq!(output, &format!("{}", input));
         }
     }
 
+    #[test]
+    /// the variant prefix only applies to Display, Debug is unchanged
+    fn error_debug_has_no_prefix_test() {
+        assert_eq!(
+            "IoError(\"foo\")",
+            format!("{:?}", HolochainError::IoError(String::from("foo"))),
+        );
+    }
+
+    #[test]
+    /// message() is the Display output without the variant prefix
+    fn error_message_test() {
+        assert_eq!("foo", HolochainError::IoError(String::from("foo")).message());
+        assert_eq!(
+            "foo",
+            String::from(HolochainError::IoError(String::from("foo")))
+        );
+        assert_eq!("timeout", HolochainError::Timeout.message());
+        assert_eq!(
+            "[Timeout] timeout; [ErrorGeneric] foo",
+            HolochainError::MultipleErrors(vec![
+                HolochainError::Timeout,
+                HolochainError::ErrorGeneric(String::from("foo")),
+            ])
+            .message(),
+        );
+    }
+
+    #[test]
+    fn error_generic_from_str_test() {
+        fn fails(message: &str) -> Result<(), HolochainError> {
+            Err(message.into())
+        }
+        fn fails_owned(message: String) -> Result<(), HolochainError> {
+            Err(message.into())
+        }
+
+        assert_eq!(
+            Err(HolochainError::ErrorGeneric("foo".to_string())),
+            fails(&String::from("foo")),
+        );
+        assert_eq!(
+            Err(HolochainError::ErrorGeneric("bar".to_string())),
+            fails_owned("bar".to_string()),
+        );
+    }
+
+    #[test]
+    fn error_predicates_test() {
+        let serialization = HolochainError::SerializationError("foo".into());
+        let io = HolochainError::IoError("foo".into());
+        let ribosome = HolochainError::Ribosome(RibosomeErrorCode::OutOfMemory);
+        let ribosome_failed = HolochainError::RibosomeFailed("foo".into());
+        let timeout = HolochainError::Timeout;
+        let generic = HolochainError::ErrorGeneric("foo".into());
+
+        assert!(serialization.is_serialization_error());
+        assert!(io.is_io_error());
+        assert!(ribosome.is_ribosome_error());
+        assert!(ribosome_failed.is_ribosome_error());
+        assert!(timeout.is_timeout());
+
+        for error in vec![&io, &ribosome, &timeout, &generic] {
+            assert!(!error.is_serialization_error());
+        }
+        for error in vec![&serialization, &ribosome, &timeout, &generic] {
+            assert!(!error.is_io_error());
+        }
+        for error in vec![&serialization, &io, &timeout, &generic] {
+            assert!(!error.is_ribosome_error());
+        }
+        for error in vec![&serialization, &io, &ribosome_failed, &generic] {
+            assert!(!error.is_timeout());
+        }
+    }
+
+    #[test]
+    fn collect_errors_test() {
+        assert_eq!(
+            Ok(vec![1, 2]),
+            HolochainError::collect_errors(vec![Ok(1), Ok(2)]),
+        );
+
+        assert_eq!(
+            Err(HolochainError::Timeout),
+            HolochainError::collect_errors(vec![Ok(1), Err(HolochainError::Timeout)]),
+        );
+
+        let error = HolochainError::collect_errors(vec![
+            Err(HolochainError::Timeout),
+            Ok(1),
+            Err(HolochainError::DnaMissing),
+        ])
+        .unwrap_err();
+        assert_eq!(
+            HolochainError::MultipleErrors(vec![
+                HolochainError::Timeout,
+                HolochainError::DnaMissing
+            ]),
+            error,
+        );
+        assert_eq!(
+            Some(&[HolochainError::Timeout, HolochainError::DnaMissing][..]),
+            error.errors(),
+        );
+        assert_eq!(None, HolochainError::Timeout.errors());
+
+        let empty: Vec<Result<(), HolochainError>> = vec![];
+        assert_eq!(Ok(vec![]), HolochainError::collect_errors(empty));
+    }
+
+    #[test]
+    fn into_ribosome_return_test() {
+        assert_eq!(
+            RibosomeEncodedValue::Failure(RibosomeErrorCode::OutOfMemory),
+            HolochainError::Ribosome(RibosomeErrorCode::OutOfMemory).into_ribosome_return(),
+        );
+        assert_eq!(
+            RibosomeEncodedValue::Failure(RibosomeErrorCode::Unspecified),
+            HolochainError::ErrorGeneric("foo".to_string()).into_ribosome_return(),
+        );
+
+        let result: Result<RibosomeEncodedValue, HolochainError> =
+            Err(HolochainError::Ribosome(RibosomeErrorCode::CallbackFailed));
+        assert_eq!(
+            RibosomeEncodedValue::Failure(RibosomeErrorCode::CallbackFailed),
+            result.unwrap_or_else(|e| e.into_ribosome_return()),
+        );
+    }
+
+    #[test]
+    fn expected_got_test() {
+        let error = HolochainError::expected_got("foo", 42);
+        assert_eq!(
+            HolochainError::ErrorGeneric("Expected foo but got 42".to_string()),
+            error,
+        );
+        let message = error.to_string();
+        assert!(message.contains("foo"));
+        assert!(message.contains("42"));
+    }
+
     #[test]
     fn core_error_to_string() {
         let error =
@@ -0,0 +1,144 @@
+//! Stable numeric codes for HolochainError variants
+//! e.g. for errors passed over RPC or stored in the DHT, where the variant must be identified
+//! without depending on the Rust enum layout
+//!
+//! The codes are stable API: a code is never reused or renumbered, new variants take the next
+//! unused code and removed variants leave a gap.
+
+use crate::error::HolochainError::{self, *};
+
+/// every HolochainError variant and its code, in code order
+#[rustfmt::skip]
+pub const ERROR_CODE_TABLE: &[(u16, &str)] = &[
+    (1, "ErrorGeneric"),
+    (2, "NotImplemented"),
+    (3, "LoggingError"),
+    (4, "DnaMissing"),
+    (5, "Dna"),
+    (6, "IoError"),
+    (7, "SerializationError"),
+    (8, "InvalidOperationOnSysEntry"),
+    (9, "CapabilityCheckFailed"),
+    (10, "ValidationFailed"),
+    (11, "Validation"),
+    (12, "Ribosome"),
+    (13, "RibosomeFailed"),
+    (14, "ConfigError"),
+    (15, "Timeout"),
+    (16, "MultipleErrors"),
+];
+
+impl HolochainError {
+    /// the stable code of the variant, @see ERROR_CODE_TABLE
+    pub fn error_code(&self) -> u16 {
+        match self {
+            ErrorGeneric(_) => 1,
+            NotImplemented(_) => 2,
+            LoggingError => 3,
+            DnaMissing => 4,
+            Dna(_) => 5,
+            IoError(_) => 6,
+            SerializationError(_) => 7,
+            InvalidOperationOnSysEntry => 8,
+            CapabilityCheckFailed => 9,
+            ValidationFailed(_) => 10,
+            Validation(_) => 11,
+            Ribosome(_) => 12,
+            RibosomeFailed(_) => 13,
+            ConfigError(_) => 14,
+            Timeout => 15,
+            MultipleErrors(_) => 16,
+        }
+    }
+
+    /// inverse of error_code(), the code carries no payload so messages are empty and
+    /// MultipleErrors has no errors
+    /// Err for unknown codes and for Dna, Validation and Ribosome whose payload can't be empty
+    pub fn from_error_code(code: u16) -> Result<HolochainError, HolochainError> {
+        match code {
+            1 => Ok(ErrorGeneric(String::new())),
+            2 => Ok(NotImplemented(String::new())),
+            3 => Ok(LoggingError),
+            4 => Ok(DnaMissing),
+            6 => Ok(IoError(String::new())),
+            7 => Ok(SerializationError(String::new())),
+            8 => Ok(InvalidOperationOnSysEntry),
+            9 => Ok(CapabilityCheckFailed),
+            10 => Ok(ValidationFailed(String::new())),
+            13 => Ok(RibosomeFailed(String::new())),
+            14 => Ok(ConfigError(String::new())),
+            15 => Ok(Timeout),
+            16 => Ok(MultipleErrors(Vec::new())),
+            5 | 11 | 12 => Err(ErrorGeneric(format!(
+                "error code {} can't be decoded without its payload",
+                code
+            ))),
+            _ => Err(ErrorGeneric(format!("unknown error code {}", code))),
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+
+    use super::*;
+    use crate::error::{DnaError, RibosomeErrorCode, ValidationError};
+    use std::collections::HashSet;
+
+    fn examples() -> Vec<HolochainError> {
+        vec![
+            ErrorGeneric("foo".into()),
+            NotImplemented("foo".into()),
+            LoggingError,
+            DnaMissing,
+            Dna(DnaError::ZomeNotFound("foo".into())),
+            IoError("foo".into()),
+            SerializationError("foo".into()),
+            InvalidOperationOnSysEntry,
+            CapabilityCheckFailed,
+            ValidationFailed("foo".into()),
+            Validation(ValidationError::new("foo", "bar", None)),
+            Ribosome(RibosomeErrorCode::OutOfMemory),
+            RibosomeFailed("foo".into()),
+            ConfigError("foo".into()),
+            Timeout,
+            MultipleErrors(vec![Timeout]),
+        ]
+    }
+
+    #[test]
+    fn error_code_table_test() {
+        let examples = examples();
+        assert_eq!(ERROR_CODE_TABLE.len(), examples.len());
+
+        for (error, (code, name)) in examples.iter().zip(ERROR_CODE_TABLE) {
+            assert_eq!(*code, error.error_code());
+            assert!(format!("{:?}", error).starts_with(name));
+        }
+
+        let codes: HashSet<u16> = ERROR_CODE_TABLE.iter().map(|(code, _)| *code).collect();
+        assert_eq!(ERROR_CODE_TABLE.len(), codes.len());
+    }
+
+    #[test]
+    fn from_error_code_test() {
+        for error in examples() {
+            match HolochainError::from_error_code(error.error_code()) {
+                Ok(decoded) => assert_eq!(error.error_code(), decoded.error_code()),
+                Err(_) => assert!(match error {
+                    Dna(_) | Validation(_) | Ribosome(_) => true,
+                    _ => false,
+                }),
+            }
+        }
+
+        assert_eq!(Ok(Timeout), HolochainError::from_error_code(15));
+        assert_eq!(
+            Ok(ErrorGeneric(String::new())),
+            HolochainError::from_error_code(1)
+        );
+        assert!(HolochainError::from_error_code(0).is_err());
+        assert!(HolochainError::from_error_code(17).is_err());
+    }
+
+}
@@ -3,6 +3,8 @@
 
 mod dna_error;
 pub mod error;
+pub mod error_registry;
 mod ribosome_error;
+mod validation_error;
 
-pub use self::{dna_error::*, error::*, ribosome_error::*};
+pub use self::{dna_error::*, error::*, ribosome_error::*, validation_error::*};
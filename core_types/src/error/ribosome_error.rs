@@ -1,8 +1,15 @@
 use self::{RibosomeEncodedValue::*, RibosomeErrorCode::*};
 use crate::{error::HolochainError, json::JsonString};
-use bits_n_pieces::u64_split_bits;
+use bits_n_pieces::{u64_merge_bits, u64_split_bits};
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
-use std::{convert::TryFrom, str::FromStr};
+use std::{
+    cmp::Ordering,
+    collections::HashMap,
+    convert::TryFrom,
+    fmt, io,
+    str::FromStr,
+    sync::{PoisonError, RwLock},
+};
 
 /// size of the integer that encodes ribosome codes
 pub type RibosomeEncodingBits = u64;
@@ -11,7 +18,7 @@ pub type RibosomeRuntimeBits = i64;
 /// size of the integer that represents a ribosome code
 pub type RibosomeCodeBits = u32;
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, PartialEq)]
 pub struct RibosomeEncodedAllocation(RibosomeEncodingBits);
 
 impl From<RibosomeEncodedAllocation> for RibosomeEncodingBits {
@@ -20,22 +27,83 @@ impl From<RibosomeEncodedAllocation> for RibosomeEncodingBits {
     }
 }
 
+/// unchecked, the bits may not represent a valid allocation and may even decode as a Failure
+/// @see RibosomeEncodedAllocation::try_new
+/// @see RibosomeEncodedValue::allocation
 impl From<RibosomeEncodingBits> for RibosomeEncodedAllocation {
     fn from(i: RibosomeEncodingBits) -> Self {
         Self(i)
     }
 }
 
+impl RibosomeEncodedAllocation {
+    /// checked construction from bits that must encode a valid allocation
+    /// zero low bits (length) would decode as Success or a Failure so are NotAnAllocation
+    /// an allocation extending past the max memory is OutOfMemory
+    pub fn try_new(bits: RibosomeEncodingBits) -> Result<Self, RibosomeErrorCode> {
+        let (offset, length) = u64_split_bits(bits);
+        if length == 0 {
+            Err(NotAnAllocation)
+        } else if offset.checked_add(length).is_none() {
+            Err(OutOfMemory)
+        } else {
+            Ok(Self(bits))
+        }
+    }
+
+    /// the high bits of the encoding, infallible as the bits are already encoded
+    /// the offset is a wasm_utils Offset but core_types can't depend on wasm_utils
+    pub fn offset(&self) -> u32 {
+        u64_split_bits(self.0).0
+    }
+
+    /// the low bits of the encoding, @see offset()
+    pub fn length(&self) -> u32 {
+        u64_split_bits(self.0).1
+    }
+}
+
 impl ToString for RibosomeEncodedAllocation {
     fn to_string(&self) -> String {
         RibosomeEncodingBits::from(self.to_owned()).to_string()
     }
 }
 
+// shows the decoded offset and length alongside the raw bits so log lines are actionable
+impl fmt::Debug for RibosomeEncodedAllocation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("RibosomeEncodedAllocation")
+            .field("bits", &format_args!("{:#018x}", self.0))
+            .field("offset", &self.offset())
+            .field("length", &self.length())
+            .finish()
+    }
+}
+
+// bit level formatting for debugging the encoding e.g. format!("{:#066b}", allocation)
+impl fmt::Binary for RibosomeEncodedAllocation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Binary::fmt(&self.0, f)
+    }
+}
+
+impl fmt::LowerHex for RibosomeEncodedAllocation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::LowerHex::fmt(&self.0, f)
+    }
+}
+
+impl fmt::UpperHex for RibosomeEncodedAllocation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::UpperHex::fmt(&self.0, f)
+    }
+}
+
 /// Represents all possible values passed to/from wasmi functions
 /// All wasmi functions are I64 values
 #[repr(u64)]
 #[derive(Clone, Debug, PartialEq)]
+#[must_use = "ribosome return codes must be returned to the WASM guest"]
 pub enum RibosomeEncodedValue {
     /// @TODO make this unambiguous or remove
     /// Contextually represents:
@@ -77,7 +145,9 @@ impl From<RibosomeEncodingBits> for RibosomeEncodedValue {
         } else {
             let (code_int, maybe_allocation_length) = u64_split_bits(i);
             if maybe_allocation_length == 0 {
-                RibosomeEncodedValue::Failure(RibosomeErrorCode::from_code_int(code_int))
+                RibosomeEncodedValue::Failure(
+                    RibosomeErrorCode::from_code_bits(code_int).unwrap_or(Unspecified),
+                )
             } else {
                 RibosomeEncodedValue::Allocation(RibosomeEncodedAllocation(i))
             }
@@ -90,7 +160,8 @@ impl ToString for RibosomeEncodedValue {
         match self {
             Success => "Success".to_string(),
             Allocation(allocation) => allocation.to_string(),
-            Failure(code) => code.to_string(),
+            // as_str() rather than to_string() as from_str() must parse it back
+            Failure(code) => code.as_str().to_string(),
         }
     }
 }
@@ -104,10 +175,22 @@ impl From<RibosomeEncodedValue> for String {
 impl FromStr for RibosomeEncodedValue {
     type Err = HolochainError;
 
+    /// inverse of to_string()
+    /// Allocations are strings of their decimal bits so anything numeric is an Allocation
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         Ok(match s.as_ref() {
             "Success" => RibosomeEncodedValue::Success,
-            _ => RibosomeEncodedValue::Failure(s.parse()?),
+            _ => match s.parse::<RibosomeEncodingBits>() {
+                Ok(bits) => RibosomeEncodedValue::Allocation(
+                    RibosomeEncodedAllocation::try_new(bits).map_err(|_| {
+                        HolochainError::ErrorGeneric(format!(
+                            "{} does not encode a valid allocation",
+                            s
+                        ))
+                    })?,
+                ),
+                Err(_) => RibosomeEncodedValue::Failure(s.parse()?),
+            },
         })
     }
 }
@@ -132,31 +215,261 @@ impl TryFrom<JsonString> for RibosomeEncodedValue {
     }
 }
 
+/// @see RibosomeEncodedValue::allocation_or_err
+impl From<RibosomeEncodedValue> for Result<RibosomeEncodedAllocation, HolochainError> {
+    fn from(ribosome_encoded_value: RibosomeEncodedValue) -> Self {
+        ribosome_encoded_value.allocation_or_err()
+    }
+}
+
 impl RibosomeEncodedValue {
     pub fn from_error(err_code: RibosomeErrorCode) -> Self {
         Failure(err_code)
     }
+
+    /// the sanctioned way to build an Allocation, the raw From<RibosomeEncodingBits> can hold
+    /// bits that decode as a Failure
+    /// same checks as WasmAllocation::new, which core_types can't depend on
+    /// - ZeroSizedAllocation for a zero length
+    /// - OutOfMemory if the allocation extends past the max memory
+    pub fn allocation(offset: u32, length: u32) -> Result<Self, RibosomeErrorCode> {
+        if length == 0 {
+            Err(ZeroSizedAllocation)
+        } else {
+            RibosomeEncodedAllocation::try_new(u64_merge_bits(offset, length)).map(Allocation)
+        }
+    }
+
+    /// same as RibosomeEncodingBits::from(self), named for the direction of the conversion
+    /// e.g. return value.into_encoded_bits() from a host function
+    pub fn into_encoded_bits(self) -> RibosomeEncodingBits {
+        RibosomeEncodingBits::from(self)
+    }
+
+    /// same as RibosomeEncodedValue::from(bits), @see into_encoded_bits()
+    pub fn from_encoded_bits(bits: RibosomeEncodingBits) -> Self {
+        RibosomeEncodedValue::from(bits)
+    }
+
+    /// the final return value of a host function as wasm sees it, same as
+    /// From<RibosomeEncodedValue> for RibosomeRuntimeBits
+    /// the bits are reinterpreted, not converted, so a negative value is not an error: the high
+    /// bit being set means an allocation with a large offset, as wasm integers are unsigned
+    /// until an instruction interprets them
+    pub fn to_runtime_bits(self) -> RibosomeRuntimeBits {
+        RibosomeEncodingBits::from(self) as RibosomeRuntimeBits
+    }
+
+    /// process exit code for CLI tools that call zome functions
+    /// - 0 for Success
+    /// - 1 for a Failure caused by the zome, @see RibosomeErrorCode::is_user_error
+    /// - 2 for a Failure caused by the host, @see RibosomeErrorCode::is_host_error
+    /// - 3 for an Allocation, which should have been read rather than exit
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Success => 0,
+            Failure(err_code) => {
+                if err_code.is_user_error() {
+                    1
+                } else {
+                    2
+                }
+            }
+            Allocation(_) => 3,
+        }
+    }
+
+    /// coarse comparison for tests that only care how a call returned
+    /// any two Allocations are the same kind whatever their bits, Failures must share a code
+    /// PartialEq remains an exact comparison
+    pub fn same_kind(&self, other: &RibosomeEncodedValue) -> bool {
+        match (self, other) {
+            (Success, Success) => true,
+            (Allocation(_), Allocation(_)) => true,
+            (Failure(a), Failure(b)) => a == b,
+            _ => false,
+        }
+    }
+
+    /// Err(HolochainError::Ribosome(..)) for a Failure, otherwise the value unchanged
+    /// e.g. RibosomeEncodedValue::from(bits).propagate_err()? in host code
+    pub fn propagate_err(self) -> Result<RibosomeEncodedValue, HolochainError> {
+        match self {
+            Failure(err_code) => Err(HolochainError::Ribosome(err_code)),
+            _ => Ok(self),
+        }
+    }
+
+    /// the encoded allocation, or the error that the value represents
+    /// Success is treated as a zero sized allocation
+    pub fn allocation_or_err(self) -> Result<RibosomeEncodedAllocation, HolochainError> {
+        match self {
+            Success => Err(HolochainError::Ribosome(ZeroSizedAllocation)),
+            Allocation(allocation) => Ok(allocation),
+            Failure(err_code) => Err(HolochainError::Ribosome(err_code)),
+        }
+    }
+
+    /// canonical one line form for debugging wire captures, unlike to_string() it is lossless
+    /// and readable for every variant
+    /// e.g. RC{success}, RC{fail:3}, RC{alloc:off=12,len=34}
+    pub fn to_wire_string(&self) -> String {
+        match self {
+            Success => "RC{success}".to_string(),
+            Failure(err_code) => format!("RC{{fail:{}}}", u64_split_bits(err_code.to_u64()).0),
+            Allocation(allocation) => format!(
+                "RC{{alloc:off={},len={}}}",
+                allocation.offset(),
+                allocation.length()
+            ),
+        }
+    }
+
+    /// inverse of to_wire_string()
+    /// Err for anything to_wire_string() can't produce, including unknown codes and invalid
+    /// allocations
+    pub fn from_wire_string(s: &str) -> Result<Self, HolochainError> {
+        let err = || HolochainError::ErrorGeneric(format!("{} is not a wire return code", s));
+        if !(s.starts_with("RC{") && s.ends_with('}')) {
+            return Err(err());
+        }
+        let body = &s[3..s.len() - 1];
+        if body == "success" {
+            Ok(Success)
+        } else if body.starts_with("fail:") {
+            let code = body["fail:".len()..]
+                .parse::<RibosomeCodeBits>()
+                .map_err(|_| err())?;
+            RibosomeErrorCode::from_code_bits(code)
+                .map(Failure)
+                .map_err(|_| err())
+        } else if body.starts_with("alloc:off=") {
+            let mut parts = body["alloc:off=".len()..].splitn(2, ",len=");
+            let mut next_int = || -> Result<u32, HolochainError> {
+                parts
+                    .next()
+                    .and_then(|part| part.parse::<u32>().ok())
+                    .ok_or_else(err)
+            };
+            let offset = next_int()?;
+            let length = next_int()?;
+            RibosomeEncodedAllocation::try_new(u64_merge_bits(offset, length))
+                .map(Allocation)
+                .map_err(|_| err())
+        } else {
+            Err(err())
+        }
+    }
+}
+
+/// The raw encoding of every RibosomeErrorCode as plain constants
+/// for guest code that needs the numbers without the enum and its conversions
+/// RibosomeErrorCode discriminants are defined by these so they can't drift
+pub mod codes {
+    use super::RibosomeEncodingBits;
+
+    pub const UNSPECIFIED: RibosomeEncodingBits = 1 << 32;
+    pub const ARGUMENT_DESERIALIZATION_FAILED: RibosomeEncodingBits = 2 << 32;
+    pub const OUT_OF_MEMORY: RibosomeEncodingBits = 3 << 32;
+    pub const RECEIVED_WRONG_ACTION_RESULT: RibosomeEncodingBits = 4 << 32;
+    pub const CALLBACK_FAILED: RibosomeEncodingBits = 5 << 32;
+    pub const RECURSIVE_CALL_FORBIDDEN: RibosomeEncodingBits = 6 << 32;
+    pub const RESPONSE_SERIALIZATION_FAILED: RibosomeEncodingBits = 7 << 32;
+    pub const NOT_AN_ALLOCATION: RibosomeEncodingBits = 8 << 32;
+    pub const ZERO_SIZED_ALLOCATION: RibosomeEncodingBits = 9 << 32;
+    pub const UNKNOWN_ENTRY_TYPE: RibosomeEncodingBits = 10 << 32;
+    // 11 and 12 are unassigned
+    pub const IO_ERROR: RibosomeEncodingBits = 13 << 32;
+    // 14 and 15 are unassigned, networking codes start at 16
+    pub const NETWORK_ERROR: RibosomeEncodingBits = 16 << 32;
 }
 
+/// (name, code, description) of every RibosomeErrorCode for generating documentation
+/// name is the variant, code is the unshifted int, description is as_str()
+#[rustfmt::skip]
+pub const DOCUMENTED_CODES: &[(&str, RibosomeCodeBits, &str)] = &[
+    ("Unspecified",                    1, "Unspecified"),
+    ("ArgumentDeserializationFailed",  2, "Argument deserialization failed"),
+    ("OutOfMemory",                    3, "Out of memory"),
+    ("ReceivedWrongActionResult",      4, "Received wrong action result"),
+    ("CallbackFailed",                 5, "Callback failed"),
+    ("RecursiveCallForbidden",         6, "Recursive call forbidden"),
+    ("ResponseSerializationFailed",    7, "Response serialization failed"),
+    ("NotAnAllocation",                8, "Not an allocation"),
+    ("ZeroSizedAllocation",            9, "Zero-sized allocation"),
+    ("UnknownEntryType",              10, "Unknown entry type"),
+    ("IoError",                       13, "IO error"),
+    ("NetworkError",                  16, "Network error"),
+];
+
 /// Enum of all possible ERROR codes that a Zome API Function could return.
 #[repr(u64)]
-#[derive(Clone, Debug, PartialEq, Eq, Hash, DefaultJson, PartialOrd, Ord)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash, DefaultJson)]
 #[rustfmt::skip]
 pub enum RibosomeErrorCode {
-    Unspecified                     = 1 << 32,
-    ArgumentDeserializationFailed   = 2 << 32,
-    OutOfMemory                     = 3 << 32,
-    ReceivedWrongActionResult       = 4 << 32,
-    CallbackFailed                  = 5 << 32,
-    RecursiveCallForbidden          = 6 << 32,
-    ResponseSerializationFailed     = 7 << 32,
-    NotAnAllocation                 = 8 << 32,
-    ZeroSizedAllocation             = 9 << 32,
-    UnknownEntryType                = 10 << 32,
+    Unspecified                     = codes::UNSPECIFIED,
+    ArgumentDeserializationFailed   = codes::ARGUMENT_DESERIALIZATION_FAILED,
+    OutOfMemory                     = codes::OUT_OF_MEMORY,
+    ReceivedWrongActionResult       = codes::RECEIVED_WRONG_ACTION_RESULT,
+    CallbackFailed                  = codes::CALLBACK_FAILED,
+    RecursiveCallForbidden          = codes::RECURSIVE_CALL_FORBIDDEN,
+    ResponseSerializationFailed     = codes::RESPONSE_SERIALIZATION_FAILED,
+    NotAnAllocation                 = codes::NOT_AN_ALLOCATION,
+    ZeroSizedAllocation             = codes::ZERO_SIZED_ALLOCATION,
+    UnknownEntryType                = codes::UNKNOWN_ENTRY_TYPE,
+    /// an I/O failure on the host e.g. persisting a DHT entry to disk
+    /// retriable in that the same zome call may succeed once the host has recovered
+    IoError                         = codes::IO_ERROR,
+    /// DHT and networking failures e.g. a peer timing out or an unreachable DHT
+    NetworkError                    = codes::NETWORK_ERROR,
 }
 
 #[rustfmt::skip]
 impl RibosomeErrorCode {
+    /// every variant in code order
+    pub fn all() -> &'static [RibosomeErrorCode] {
+        &[
+            Unspecified,
+            ArgumentDeserializationFailed,
+            OutOfMemory,
+            ReceivedWrongActionResult,
+            CallbackFailed,
+            RecursiveCallForbidden,
+            ResponseSerializationFailed,
+            NotAnAllocation,
+            ZeroSizedAllocation,
+            UnknownEntryType,
+            IoError,
+            NetworkError,
+        ]
+    }
+
+    /// iterates every variant in code order so tests and tooling don't hardcode the count
+    pub fn iter() -> impl Iterator<Item = RibosomeErrorCode> {
+        RibosomeErrorCode::all().iter().cloned()
+    }
+
+    /// the number of variants, e.g. to size a [u64; RibosomeErrorCode::COUNT] of counters
+    pub const COUNT: usize = 12;
+
+    /// dense zero based index of the variant in code order, unlike the sparse discriminants
+    /// always less than COUNT
+    pub fn index(&self) -> usize {
+        RibosomeErrorCode::all()
+            .iter()
+            .position(|code| code == self)
+            .expect("every RibosomeErrorCode is in all()")
+    }
+
+    /// inverse of index(), None for an index of COUNT or more
+    pub fn from_index(index: usize) -> Option<Self> {
+        RibosomeErrorCode::all().get(index).cloned()
+    }
+
+    /// the canonical English message, used for the wire format i.e. serialization, from_str()
+    /// and the string forms of RibosomeEncodedValue, and for every error payload e.g. the
+    /// message of HolochainError and io::Error
+    /// only logs and RibosomeErrorReport use the operator's messages, @see localized
     pub fn as_str(&self) -> &str {
         match self {
             Unspecified                     => "Unspecified",
@@ -169,10 +482,68 @@ impl RibosomeErrorCode {
             NotAnAllocation                 => "Not an allocation",
             ZeroSizedAllocation             => "Zero-sized allocation",
             UnknownEntryType                => "Unknown entry type",
+            IoError                         => "IO error",
+            NetworkError                    => "Network error",
+        }
+    }
+}
+
+impl RibosomeErrorCode {
+    /// errors caused by the logic of the wasm guest, e.g. a zome doing something it is not
+    /// allowed to do or getting its own data wrong
+    /// all other errors are host errors, i.e. caused by the ribosome, the memory shared with the
+    /// guest or an unknown cause, because the guest can't reasonably be expected to avoid them
+    /// ArgumentDeserializationFailed and CallbackFailed are host errors as they are often caused
+    /// by what the host sent or how it ran the callback
+    /// the conductor can log user errors at a lower severity than host errors
+    pub fn is_user_error(&self) -> bool {
+        match self {
+            ReceivedWrongActionResult | RecursiveCallForbidden | UnknownEntryType => true,
+            Unspecified
+            | ArgumentDeserializationFailed
+            | OutOfMemory
+            | ResponseSerializationFailed
+            | NotAnAllocation
+            | ZeroSizedAllocation
+            | CallbackFailed
+            | IoError
+            | NetworkError => false,
+        }
+    }
+
+    /// every error is exactly one of a user or host error
+    /// @see is_user_error
+    pub fn is_host_error(&self) -> bool {
+        !self.is_user_error()
+    }
+
+    /// user errors are recoverable by fixing the guest, host errors are fatal to the call
+    pub fn severity(&self) -> RibosomeErrorSeverity {
+        if self.is_user_error() {
+            RibosomeErrorSeverity::Recoverable
+        } else {
+            RibosomeErrorSeverity::Fatal
+        }
+    }
+
+    /// the more severe of two errors, ties favour self
+    /// e.g. to report a single deterministic error for a composite call
+    pub fn merge(self, other: RibosomeErrorCode) -> RibosomeErrorCode {
+        if other.severity() > self.severity() {
+            other
+        } else {
+            self
         }
     }
 }
 
+/// ordered from least to most severe
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum RibosomeErrorSeverity {
+    Recoverable,
+    Fatal,
+}
+
 impl From<HolochainError> for RibosomeErrorCode {
     fn from(error: HolochainError) -> RibosomeErrorCode {
         // the mapping between HolochainError and RibosomeErrorCode is pretty poor overall
@@ -182,47 +553,241 @@ impl From<HolochainError> for RibosomeErrorCode {
             HolochainError::LoggingError => RibosomeErrorCode::Unspecified,
             HolochainError::DnaMissing => RibosomeErrorCode::Unspecified,
             HolochainError::Dna(_) => RibosomeErrorCode::Unspecified,
-            HolochainError::IoError(_) => RibosomeErrorCode::Unspecified,
+            HolochainError::IoError(_) => RibosomeErrorCode::IoError,
             HolochainError::SerializationError(_) => {
                 RibosomeErrorCode::ArgumentDeserializationFailed
             }
             HolochainError::InvalidOperationOnSysEntry => RibosomeErrorCode::UnknownEntryType,
             HolochainError::CapabilityCheckFailed => RibosomeErrorCode::Unspecified,
             HolochainError::ValidationFailed(_) => RibosomeErrorCode::CallbackFailed,
+            HolochainError::Validation(_) => RibosomeErrorCode::CallbackFailed,
             HolochainError::Ribosome(e) => e,
             HolochainError::RibosomeFailed(_) => RibosomeErrorCode::CallbackFailed,
             HolochainError::ConfigError(_) => RibosomeErrorCode::Unspecified,
             HolochainError::Timeout => RibosomeErrorCode::Unspecified,
+            HolochainError::MultipleErrors(_) => RibosomeErrorCode::Unspecified,
+        }
+    }
+}
+
+/// the HolochainError variants that From<HolochainError> for RibosomeErrorCode collapses to
+/// Unspecified, i.e. the ones a zome can't tell apart
+/// every variant must either map to a distinct code or be listed here, see lossy_mappings_test
+#[cfg(any(test, debug_assertions))]
+pub fn lossy_mappings() -> Vec<&'static str> {
+    vec![
+        "ErrorGeneric",
+        "LoggingError",
+        "DnaMissing",
+        "Dna",
+        "CapabilityCheckFailed",
+        "ConfigError",
+        "Timeout",
+        "MultipleErrors",
+    ]
+}
+
+/// ordered by discriminant, which roughly correlates with severity
+/// explicit rather than derived so that reordering the variants can't change the ordering
+impl Ord for RibosomeErrorCode {
+    fn cmp(&self, other: &RibosomeErrorCode) -> Ordering {
+        (self.clone() as RibosomeEncodingBits).cmp(&(other.clone() as RibosomeEncodingBits))
+    }
+}
+
+impl PartialOrd for RibosomeErrorCode {
+    fn partial_cmp(&self, other: &RibosomeErrorCode) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// a RibosomeErrorCode with key/value context for operators, e.g. the zome and function
+/// only for reporting, the code alone is what goes over the wire
+#[derive(Clone, Debug, PartialEq)]
+pub struct RibosomeErrorReport {
+    pub code: RibosomeErrorCode,
+    pub context: Vec<(String, String)>,
+}
+
+impl RibosomeErrorReport {
+    pub fn new(code: RibosomeErrorCode) -> Self {
+        RibosomeErrorReport {
+            code,
+            context: Vec::new(),
         }
     }
+
+    /// adds a key/value pair, pairs are rendered in the order they are added
+    pub fn with_context<K: Into<String>, V: Into<String>>(mut self, key: K, value: V) -> Self {
+        self.context.push((key.into(), value.into()));
+        self
+    }
+}
+
+impl From<RibosomeErrorCode> for RibosomeErrorReport {
+    fn from(code: RibosomeErrorCode) -> Self {
+        RibosomeErrorReport::new(code)
+    }
+}
+
+/// e.g. "Callback failed (zome: blog, function: create_post)"
+impl fmt::Display for RibosomeErrorReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.code.localized())?;
+        if !self.context.is_empty() {
+            let context: Vec<String> = self
+                .context
+                .iter()
+                .map(|(key, value)| format!("{}: {}", key, value))
+                .collect();
+            write!(f, " ({})", context.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+/// errors that know the ribosome error code they should surface as
+/// so host code can be generic over its error types
+pub trait IntoRibosomeErrorCode {
+    fn ribosome_code(&self) -> RibosomeErrorCode;
+}
+
+impl IntoRibosomeErrorCode for RibosomeErrorCode {
+    fn ribosome_code(&self) -> RibosomeErrorCode {
+        self.clone()
+    }
+}
+
+/// @see From<HolochainError> for RibosomeErrorCode
+impl IntoRibosomeErrorCode for HolochainError {
+    fn ribosome_code(&self) -> RibosomeErrorCode {
+        RibosomeErrorCode::from(self.clone())
+    }
+}
+
+/// HolochainError::Ribosome(code) round trips through RibosomeErrorCode unchanged
+/// every other HolochainError variant is lossy through RibosomeErrorCode, their messages are
+/// dropped and several variants share a code e.g. ErrorGeneric, DnaMissing and Timeout all become
+/// Ribosome(Unspecified)
+impl From<RibosomeErrorCode> for HolochainError {
+    fn from(ribosome_error_code: RibosomeErrorCode) -> Self {
+        HolochainError::Ribosome(ribosome_error_code)
+    }
+}
+
+/// for host layers that express everything as io::Error, the message is as_str()
+/// - serialization codes are InvalidData
+/// - everything else is Other, including OutOfMemory as io::ErrorKind::OutOfMemory doesn't
+///   exist on the pinned toolchain
+impl From<RibosomeErrorCode> for io::Error {
+    fn from(ribosome_error_code: RibosomeErrorCode) -> Self {
+        let kind = match ribosome_error_code {
+            ArgumentDeserializationFailed | ResponseSerializationFailed => {
+                io::ErrorKind::InvalidData
+            }
+            _ => io::ErrorKind::Other,
+        };
+        io::Error::new(kind, ribosome_error_code.as_str())
+    }
+}
+
+lazy_static! {
+    /// operator overrides of the as_str() messages, @see RibosomeErrorCode::set_messages
+    static ref MESSAGES: RwLock<HashMap<RibosomeErrorCode, String>> = RwLock::new(HashMap::new());
+}
+
+impl RibosomeErrorCode {
+    /// installs messages that localized() and to_string() use instead of the English as_str()
+    /// replaces any previous table, codes missing from the table fall back to as_str()
+    /// the table is process wide
+    /// error payloads and the wire format always use as_str() so are unaffected
+    pub fn set_messages(table: HashMap<RibosomeErrorCode, String>) {
+        *MESSAGES.write().unwrap_or_else(PoisonError::into_inner) = table;
+    }
+
+    /// the operator's message for this code if any, otherwise as_str(), @see set_messages
+    /// only for text read by people e.g. logs, never for a payload that callers match on
+    pub fn localized(&self) -> String {
+        MESSAGES
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(self)
+            .cloned()
+            .unwrap_or_else(|| self.as_str().to_string())
+    }
 }
 
 impl ToString for RibosomeErrorCode {
     fn to_string(&self) -> String {
-        self.as_str().to_string()
+        self.localized()
     }
 }
 
 impl From<RibosomeErrorCode> for String {
     fn from(ribosome_error_code: RibosomeErrorCode) -> Self {
-        ribosome_error_code.to_string()
+        ribosome_error_code.as_str().to_string()
     }
 }
 
 impl RibosomeErrorCode {
+    /// the full shifted discriminant as it is carried on the wire
+    /// e.g. 3 << 32 for OutOfMemory, @see codes
+    pub fn to_u64(&self) -> RibosomeEncodingBits {
+        self.clone() as RibosomeEncodingBits
+    }
+
+    /// inverse of to_u64, @see from_encoding_bits
+    pub fn from_u64(bits: RibosomeEncodingBits) -> Result<Self, HolochainError> {
+        RibosomeErrorCode::from_encoding_bits(bits).ok_or_else(|| {
+            HolochainError::ErrorGeneric(format!("{:#x} is not a RibosomeErrorCode", bits))
+        })
+    }
+
+    /// the unshifted code e.g. 3 for OutOfMemory
+    /// Err for 0 and unknown codes
+    pub fn from_code_bits(code: RibosomeCodeBits) -> Result<Self, HolochainError> {
+        RibosomeErrorCode::from_code_int_strict(code).ok_or_else(|| {
+            HolochainError::ErrorGeneric(format!("{} is not a RibosomeErrorCode code", code))
+        })
+    }
+
+    #[deprecated(note = "panics on 0 and maps unknown codes to Unspecified, use from_code_bits")]
     pub fn from_code_int(code: RibosomeCodeBits) -> Self {
         match code {
             0 => panic!(format!("RibosomeErrorCode == {:?} encountered", code)),
-            2 => ArgumentDeserializationFailed,
-            3 => OutOfMemory,
-            4 => ReceivedWrongActionResult,
-            5 => CallbackFailed,
-            6 => RecursiveCallForbidden,
-            7 => ResponseSerializationFailed,
-            8 => NotAnAllocation,
-            9 => ZeroSizedAllocation,
-            10 => UnknownEntryType,
-            1 | _ => Unspecified,
+            _ => RibosomeErrorCode::from_code_int_strict(code).unwrap_or(Unspecified),
+        }
+    }
+
+    /// the unshifted code e.g. 3 for OutOfMemory
+    /// None for 0 and unknown codes rather than panicking or collapsing to Unspecified, so
+    /// strict callers can tell a code from a newer version of the ribosome from Unspecified
+    /// @see from_code_bits for the same as a Result
+    pub fn from_code_int_strict(code: RibosomeCodeBits) -> Option<Self> {
+        match code {
+            1 => Some(Unspecified),
+            2 => Some(ArgumentDeserializationFailed),
+            3 => Some(OutOfMemory),
+            4 => Some(ReceivedWrongActionResult),
+            5 => Some(CallbackFailed),
+            6 => Some(RecursiveCallForbidden),
+            7 => Some(ResponseSerializationFailed),
+            8 => Some(NotAnAllocation),
+            9 => Some(ZeroSizedAllocation),
+            10 => Some(UnknownEntryType),
+            13 => Some(IoError),
+            16 => Some(NetworkError),
+            _ => None,
+        }
+    }
+
+    /// strict parse of the full shifted discriminant as it is carried on the wire
+    /// i.e. the code in the high bits, a Failure has zero low bits
+    /// None for 0, non-zero low bits and unknown codes
+    pub fn from_encoding_bits(bits: RibosomeEncodingBits) -> Option<Self> {
+        match u64_split_bits(bits) {
+            (code, 0) => RibosomeErrorCode::from_code_int_strict(code),
+            _ => None,
         }
     }
 
@@ -244,7 +809,7 @@ impl Serialize for RibosomeErrorCode {
     where
         S: Serializer,
     {
-        serializer.serialize_str(&self.to_string())
+        serializer.serialize_str(self.as_str())
     }
 }
 
@@ -275,6 +840,8 @@ impl FromStr for RibosomeErrorCode {
             "Not an allocation" => Ok(RibosomeErrorCode::NotAnAllocation),
             "Zero-sized allocation" => Ok(RibosomeErrorCode::ZeroSizedAllocation),
             "Unknown entry type" => Ok(RibosomeErrorCode::UnknownEntryType),
+            "IO error" => Ok(RibosomeErrorCode::IoError),
+            "Network error" => Ok(RibosomeErrorCode::NetworkError),
             _ => Err(HolochainError::ErrorGeneric(String::from(
                 "Unknown RibosomeErrorCode",
             ))),
@@ -285,8 +852,15 @@ impl FromStr for RibosomeErrorCode {
 #[cfg(test)]
 pub mod tests {
     use super::*;
+    use bits_n_pieces::u64_merge_bits;
+    use crate::error::{DnaError, ValidationError};
+    use std::{
+        collections::HashSet,
+        sync::{Mutex, MutexGuard},
+    };
 
     #[test]
+    #[allow(deprecated)]
     fn ribosome_error_code_round_trip() {
         let oom = RibosomeErrorCode::from_code_int(
             ((RibosomeErrorCode::OutOfMemory as u64) >> 32) as RibosomeCodeBits,
@@ -296,10 +870,27 @@ pub mod tests {
     }
 
     #[test]
-    fn error_conversion() {
-        for code in 1..=10 {
-            let mut err = RibosomeErrorCode::from_code_int(code);
+    #[allow(deprecated)]
+    fn from_code_int_strict_test() {
+        assert_eq!(
+            Some(Unspecified),
+            RibosomeErrorCode::from_code_int_strict(1)
+        );
+        assert_eq!(
+            Some(CallbackFailed),
+            RibosomeErrorCode::from_code_int_strict(5)
+        );
+        assert_eq!(None, RibosomeErrorCode::from_code_int_strict(99));
+        assert_eq!(None, RibosomeErrorCode::from_code_int_strict(0));
+
+        // the lenient version can't tell an unknown code from Unspecified
+        assert_eq!(Unspecified, RibosomeErrorCode::from_code_int(1));
+        assert_eq!(Unspecified, RibosomeErrorCode::from_code_int(99));
+    }
 
+    #[test]
+    fn error_conversion() {
+        for mut err in RibosomeErrorCode::iter() {
             let err_str = err.as_str().to_owned();
 
             err = err_str.parse().expect("unable to parse error");
@@ -311,8 +902,800 @@ pub mod tests {
         }
     }
 
+    #[test]
+    fn wire_string_round_trip_test() {
+        let allocation = RibosomeEncodedAllocation::try_new(u64_merge_bits(12, 34)).unwrap();
+        for (value, wire) in vec![
+            (Success, "RC{success}"),
+            (Failure(OutOfMemory), "RC{fail:3}"),
+            (Failure(UnknownEntryType), "RC{fail:10}"),
+            (Failure(IoError), "RC{fail:13}"),
+            (Failure(NetworkError), "RC{fail:16}"),
+            (Allocation(allocation), "RC{alloc:off=12,len=34}"),
+        ] {
+            assert_eq!(wire, value.to_wire_string());
+            assert_eq!(Ok(value), RibosomeEncodedValue::from_wire_string(wire));
+        }
+
+        for code in RibosomeErrorCode::iter() {
+            let value = Failure(code);
+            assert_eq!(
+                Ok(value.clone()),
+                RibosomeEncodedValue::from_wire_string(&value.to_wire_string()),
+            );
+        }
+    }
+
+    #[test]
+    fn from_wire_string_err_test() {
+        for wire in vec![
+            "",
+            "RC{}",
+            "RC{success",
+            "success",
+            "RC{Success}",
+            // unknown codes
+            "RC{fail:0}",
+            "RC{fail:11}",
+            "RC{fail:foo}",
+            // not allocations
+            "RC{alloc:off=12,len=0}",
+            "RC{alloc:off=4294967295,len=1}",
+            "RC{alloc:off=12}",
+            "RC{alloc:off=12,len=foo}",
+            "RC{alloc:len=34,off=12}",
+        ] {
+            assert_eq!(
+                Err(HolochainError::ErrorGeneric(format!(
+                    "{} is not a wire return code",
+                    wire
+                ))),
+                RibosomeEncodedValue::from_wire_string(wire),
+            );
+        }
+    }
+
+    #[test]
+    fn ribosome_error_code_index_test() {
+        assert_eq!(RibosomeErrorCode::COUNT, RibosomeErrorCode::all().len());
+
+        for (i, code) in RibosomeErrorCode::iter().enumerate() {
+            assert_eq!(i, code.index());
+            assert_eq!(
+                Some(code.clone()),
+                RibosomeErrorCode::from_index(code.index())
+            );
+        }
+
+        assert_eq!(0, Unspecified.index());
+        assert_eq!(RibosomeErrorCode::COUNT - 1, NetworkError.index());
+        assert_eq!(
+            None,
+            RibosomeErrorCode::from_index(RibosomeErrorCode::COUNT)
+        );
+
+        // sized counters
+        let mut counters = [0_u64; RibosomeErrorCode::COUNT];
+        counters[OutOfMemory.index()] += 1;
+        assert_eq!(1, counters.iter().sum::<u64>());
+    }
+
+    #[test]
+    fn ribosome_error_code_iter_test() {
+        let codes: Vec<RibosomeErrorCode> = RibosomeErrorCode::iter().collect();
+        assert_eq!(DOCUMENTED_CODES.len(), codes.len());
+
+        // each variant exactly once, in code order
+        let unique: HashSet<RibosomeErrorCode> = codes.iter().cloned().collect();
+        assert_eq!(codes.len(), unique.len());
+        for (code, (_, code_bits, _)) in codes.iter().zip(DOCUMENTED_CODES) {
+            assert_eq!(
+                Ok(code.clone()),
+                RibosomeErrorCode::from_code_bits(*code_bits)
+            );
+        }
+    }
+
+    #[test]
+    fn ribosome_error_report_test() {
+        let report = RibosomeErrorReport::new(CallbackFailed)
+            .with_context("zome", "blog")
+            .with_context("function", String::from("create_post"))
+            .with_context("call_id", "42");
+
+        assert_eq!(CallbackFailed, report.code);
+        assert_eq!(
+            "Callback failed (zome: blog, function: create_post, call_id: 42)",
+            report.to_string(),
+        );
+
+        assert_eq!(
+            "Out of memory",
+            RibosomeErrorReport::from(OutOfMemory).to_string(),
+        );
+    }
+
+    #[test]
+    fn into_ribosome_error_code_test() {
+        struct Dummy;
+        impl IntoRibosomeErrorCode for Dummy {
+            fn ribosome_code(&self) -> RibosomeErrorCode {
+                RecursiveCallForbidden
+            }
+        }
+
+        fn failure<E: IntoRibosomeErrorCode>(error: E) -> RibosomeEncodedValue {
+            Failure(error.ribosome_code())
+        }
+
+        assert_eq!(Failure(RecursiveCallForbidden), failure(Dummy));
+        assert_eq!(Failure(OutOfMemory), failure(OutOfMemory));
+        assert_eq!(
+            Failure(ArgumentDeserializationFailed),
+            failure(HolochainError::SerializationError("foo".into())),
+        );
+    }
+
+    #[test]
+    fn allocation_test() {
+        assert_eq!(
+            Err(ZeroSizedAllocation),
+            RibosomeEncodedValue::allocation(0, 0)
+        );
+        assert_eq!(
+            Err(ZeroSizedAllocation),
+            RibosomeEncodedValue::allocation(4, 0)
+        );
+        assert_eq!(
+            Err(OutOfMemory),
+            RibosomeEncodedValue::allocation(std::u32::MAX, 1)
+        );
+
+        for (offset, length) in vec![(0, 1), (4, 8), (std::u32::MAX - 1, 1)] {
+            let value = RibosomeEncodedValue::allocation(offset, length).unwrap();
+            assert_eq!(
+                Allocation(RibosomeEncodedAllocation::from(u64_merge_bits(
+                    offset, length
+                ))),
+                value,
+            );
+            match RibosomeEncodedValue::from_encoded_bits(value.into_encoded_bits()) {
+                Allocation(allocation) => {
+                    assert_eq!(offset, allocation.offset());
+                    assert_eq!(length, allocation.length());
+                }
+                other => panic!("{:?} is not an allocation", other),
+            }
+        }
+    }
+
+    #[test]
+    fn encoded_bits_test() {
+        let allocation = Allocation(RibosomeEncodedAllocation::from(u64_merge_bits(1, 2)));
+        for value in vec![Success, Failure(OutOfMemory), allocation] {
+            let bits = value.clone().into_encoded_bits();
+            assert_eq!(RibosomeEncodingBits::from(value.clone()), bits);
+            assert_eq!(value, RibosomeEncodedValue::from_encoded_bits(bits));
+        }
+    }
+
+    #[test]
+    fn to_runtime_bits_test() {
+        for code in RibosomeErrorCode::iter() {
+            let failure = Failure(code.clone());
+            assert_eq!(
+                failure.clone().into_encoded_bits(),
+                failure.clone().to_runtime_bits() as RibosomeEncodingBits,
+            );
+            assert_eq!(
+                RibosomeRuntimeBits::from(failure.clone()),
+                failure.to_runtime_bits(),
+            );
+        }
+        assert_eq!(0, Success.to_runtime_bits());
+
+        // the high bit is a large offset rather than a negative number
+        let allocation = Allocation(RibosomeEncodedAllocation::from(u64_merge_bits(1 << 31, 2)));
+        let runtime_bits = allocation.clone().to_runtime_bits();
+        assert!(runtime_bits < 0);
+        assert_eq!(
+            allocation,
+            RibosomeEncodedValue::from_encoded_bits(runtime_bits as RibosomeEncodingBits),
+        );
+    }
+
+    #[test]
+    fn same_kind_test() {
+        let allocation = Allocation(RibosomeEncodedAllocation::from(u64_merge_bits(1, 2)));
+        let other_allocation = Allocation(RibosomeEncodedAllocation::from(u64_merge_bits(3, 4)));
+
+        assert!(allocation.same_kind(&other_allocation));
+        assert_ne!(allocation, other_allocation);
+        assert!(Success.same_kind(&Success));
+        assert!(Failure(OutOfMemory).same_kind(&Failure(OutOfMemory)));
+
+        assert!(!Failure(OutOfMemory).same_kind(&Failure(CallbackFailed)));
+        assert!(!Success.same_kind(&allocation));
+        assert!(!allocation.same_kind(&Failure(OutOfMemory)));
+        assert!(!Failure(OutOfMemory).same_kind(&Success));
+    }
+
+    #[test]
+    fn propagate_err_test() {
+        assert_eq!(Ok(Success), Success.propagate_err());
+
+        let allocation = Allocation(RibosomeEncodedAllocation::from(u64_merge_bits(1, 2)));
+        assert_eq!(Ok(allocation.clone()), allocation.propagate_err());
+
+        assert_eq!(
+            Err(HolochainError::Ribosome(OutOfMemory)),
+            Failure(OutOfMemory).propagate_err(),
+        );
+    }
+
+    #[test]
+    fn ribosome_encoded_value_from_str_test() {
+        assert_eq!(Ok(Success), "Success".parse::<RibosomeEncodedValue>());
+        assert_eq!(
+            Ok(Failure(OutOfMemory)),
+            "Out of memory".parse::<RibosomeEncodedValue>(),
+        );
+
+        let allocation = Allocation(RibosomeEncodedAllocation::from(u64_merge_bits(1, 2)));
+        assert_eq!("4294967298", allocation.to_string());
+        assert_eq!(Ok(allocation), "4294967298".parse::<RibosomeEncodedValue>());
+
+        // numeric but zero length
+        assert_eq!(
+            Err(HolochainError::ErrorGeneric(
+                "4294967296 does not encode a valid allocation".to_string()
+            )),
+            "4294967296".parse::<RibosomeEncodedValue>(),
+        );
+
+        assert_eq!(
+            Err(HolochainError::ErrorGeneric(
+                "Unknown RibosomeErrorCode".to_string()
+            )),
+            "foo".parse::<RibosomeEncodedValue>(),
+        );
+    }
+
+    #[test]
+    fn user_and_host_error_test() {
+        // pins the classification of every code, severity() and merge() depend on it
+        for code in RibosomeErrorCode::iter() {
+            let is_user_error = match code {
+                ReceivedWrongActionResult | RecursiveCallForbidden | UnknownEntryType => true,
+                Unspecified
+                | ArgumentDeserializationFailed
+                | OutOfMemory
+                | ResponseSerializationFailed
+                | NotAnAllocation
+                | ZeroSizedAllocation
+                | CallbackFailed
+                | IoError
+                | NetworkError => false,
+            };
+            assert_eq!(is_user_error, code.is_user_error(), "{:?}", code);
+            assert_eq!(!is_user_error, code.is_host_error(), "{:?}", code);
+        }
+    }
+
+    #[test]
+    fn holochain_error_round_trip_test() {
+        for ribosome_error_code in RibosomeErrorCode::iter() {
+            let holochain_error = HolochainError::from(ribosome_error_code.clone());
+            assert_eq!(
+                HolochainError::Ribosome(ribosome_error_code.clone()),
+                holochain_error
+            );
+            assert_eq!(
+                holochain_error.clone(),
+                HolochainError::from(RibosomeErrorCode::from(holochain_error)),
+            );
+        }
+
+        // other variants are lossy
+        assert_eq!(
+            HolochainError::Ribosome(Unspecified),
+            HolochainError::from(RibosomeErrorCode::from(HolochainError::Timeout)),
+        );
+    }
+
+    #[test]
+    fn lossy_mappings_test() {
+        // exhaustive so that a new HolochainError variant won't compile until it is named here
+        // and given an example below
+        fn variant_name(error: &HolochainError) -> &'static str {
+            match error {
+                HolochainError::ErrorGeneric(_) => "ErrorGeneric",
+                HolochainError::NotImplemented(_) => "NotImplemented",
+                HolochainError::LoggingError => "LoggingError",
+                HolochainError::DnaMissing => "DnaMissing",
+                HolochainError::Dna(_) => "Dna",
+                HolochainError::IoError(_) => "IoError",
+                HolochainError::SerializationError(_) => "SerializationError",
+                HolochainError::InvalidOperationOnSysEntry => "InvalidOperationOnSysEntry",
+                HolochainError::CapabilityCheckFailed => "CapabilityCheckFailed",
+                HolochainError::ValidationFailed(_) => "ValidationFailed",
+                HolochainError::Validation(_) => "Validation",
+                HolochainError::Ribosome(_) => "Ribosome",
+                HolochainError::RibosomeFailed(_) => "RibosomeFailed",
+                HolochainError::ConfigError(_) => "ConfigError",
+                HolochainError::Timeout => "Timeout",
+                HolochainError::MultipleErrors(_) => "MultipleErrors",
+            }
+        }
+
+        let examples = vec![
+            HolochainError::ErrorGeneric("foo".into()),
+            HolochainError::NotImplemented("foo".into()),
+            HolochainError::LoggingError,
+            HolochainError::DnaMissing,
+            HolochainError::Dna(DnaError::ZomeNotFound("foo".into())),
+            HolochainError::IoError("foo".into()),
+            HolochainError::SerializationError("foo".into()),
+            HolochainError::InvalidOperationOnSysEntry,
+            HolochainError::CapabilityCheckFailed,
+            HolochainError::ValidationFailed("foo".into()),
+            HolochainError::Validation(ValidationError::new("foo", "bar", None)),
+            HolochainError::Ribosome(OutOfMemory),
+            HolochainError::RibosomeFailed("foo".into()),
+            HolochainError::ConfigError("foo".into()),
+            HolochainError::Timeout,
+            HolochainError::MultipleErrors(vec![]),
+        ];
+
+        let names: HashSet<&str> = examples.iter().map(variant_name).collect();
+        assert_eq!(examples.len(), names.len(), "one example per variant");
+
+        let lossy = lossy_mappings();
+        for name in lossy.iter() {
+            assert!(
+                names.contains(name),
+                "{} is not a HolochainError variant",
+                name
+            );
+        }
+        for error in examples {
+            let name = variant_name(&error);
+            assert_eq!(
+                lossy.contains(&name),
+                RibosomeErrorCode::from(error) == Unspecified,
+                "{} must map to a distinct code or be listed in lossy_mappings()",
+                name,
+            );
+        }
+    }
+
+    #[test]
+    fn std_io_error_from_ribosome_error_code_test() {
+        for (code, kind) in vec![
+            (ArgumentDeserializationFailed, io::ErrorKind::InvalidData),
+            (ResponseSerializationFailed, io::ErrorKind::InvalidData),
+            (OutOfMemory, io::ErrorKind::Other),
+            (CallbackFailed, io::ErrorKind::Other),
+            (IoError, io::ErrorKind::Other),
+        ] {
+            let error = io::Error::from(code.clone());
+            assert_eq!(kind, error.kind());
+            assert_eq!(code.as_str(), error.to_string());
+        }
+    }
+
+    lazy_static! {
+        /// serializes tests that install messages as the table is process wide
+        static ref MESSAGES_TEST_LOCK: Mutex<()> = Mutex::new(());
+    }
+
+    /// installs a table of messages until dropped, then restores the previous table
+    /// holds MESSAGES_TEST_LOCK throughout so no other test sees the table
+    struct MessagesGuard {
+        previous: HashMap<RibosomeErrorCode, String>,
+        _lock: MutexGuard<'static, ()>,
+    }
+
+    impl MessagesGuard {
+        fn new(table: HashMap<RibosomeErrorCode, String>) -> MessagesGuard {
+            let lock = MESSAGES_TEST_LOCK
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner);
+            let previous = MESSAGES
+                .read()
+                .unwrap_or_else(PoisonError::into_inner)
+                .clone();
+            RibosomeErrorCode::set_messages(table);
+            MessagesGuard {
+                previous,
+                _lock: lock,
+            }
+        }
+    }
+
+    impl Drop for MessagesGuard {
+        fn drop(&mut self) {
+            RibosomeErrorCode::set_messages(self.previous.clone());
+        }
+    }
+
+    #[test]
+    fn set_messages_test() {
+        let mut table = HashMap::new();
+        table.insert(NetworkError, "Netzwerkfehler".to_string());
+        let guard = MessagesGuard::new(table);
+
+        assert_eq!("Netzwerkfehler", NetworkError.to_string());
+        assert_eq!("Network error", NetworkError.as_str());
+        // codes without an override fall back to the defaults
+        assert_eq!("IO error", IoError.to_string());
+
+        // the wire format is still the canonical English
+        assert_eq!(
+            Ok(NetworkError),
+            "Network error".parse::<RibosomeErrorCode>()
+        );
+        assert!("Netzwerkfehler".parse::<RibosomeErrorCode>().is_err());
+        assert_eq!(
+            JsonString::from("\"Network error\""),
+            JsonString::from(NetworkError),
+        );
+        assert_eq!(
+            Ok(Failure(NetworkError)),
+            Failure(NetworkError).to_string().parse::<RibosomeEncodedValue>(),
+        );
+
+        // reports for people are localized
+        assert_eq!("Netzwerkfehler", NetworkError.localized());
+        assert_eq!(
+            "Netzwerkfehler",
+            RibosomeErrorReport::new(NetworkError).to_string(),
+        );
+
+        // error payloads are not, callers match on the English
+        assert_eq!(
+            "[Ribosome] Network error",
+            HolochainError::Ribosome(NetworkError).to_string(),
+        );
+        assert_eq!("Network error", io::Error::from(NetworkError).to_string());
+        assert_eq!("Network error", String::from(NetworkError));
+
+        drop(guard);
+        assert_eq!("Network error", NetworkError.to_string());
+    }
+
+    #[test]
+    fn io_error_test() {
+        assert_eq!(
+            IoError,
+            RibosomeErrorCode::from(HolochainError::IoError("disk full".to_string())),
+        );
+        assert_eq!(Ok(IoError), RibosomeErrorCode::from_code_bits(13));
+        assert_eq!(Ok(IoError), "IO error".parse::<RibosomeErrorCode>());
+        assert!(IoError.is_host_error());
+
+        let json = JsonString::from(IoError);
+        assert_eq!(JsonString::from("\"IO error\""), json);
+        assert_eq!(Ok(IoError), RibosomeErrorCode::try_from(json));
+
+        // every code round trips through its serialization
+        for code in RibosomeErrorCode::iter() {
+            assert_eq!(
+                Ok(code.clone()),
+                RibosomeErrorCode::try_from(JsonString::from(code)),
+            );
+        }
+    }
+
+    #[test]
+    fn exit_code_test() {
+        assert_eq!(0, Success.exit_code());
+        assert_eq!(1, Failure(UnknownEntryType).exit_code());
+        assert_eq!(2, Failure(OutOfMemory).exit_code());
+        assert_eq!(
+            3,
+            Allocation(RibosomeEncodedAllocation::from(u64_merge_bits(1, 2))).exit_code(),
+        );
+
+        for code in RibosomeErrorCode::iter() {
+            assert_ne!(0, Failure(code).exit_code());
+        }
+    }
+
+    #[test]
+    fn allocation_or_err_test() {
+        assert_eq!(
+            Err(HolochainError::Ribosome(ZeroSizedAllocation)),
+            Success.allocation_or_err(),
+        );
+
+        assert_eq!(
+            Err(HolochainError::Ribosome(OutOfMemory)),
+            Failure(OutOfMemory).allocation_or_err(),
+        );
+
+        let allocation = RibosomeEncodedAllocation::from(u64_merge_bits(1, 2));
+        assert_eq!(
+            Ok(allocation.clone()),
+            Allocation(allocation).allocation_or_err(),
+        );
+    }
+
+    #[test]
+    fn result_from_ribosome_encoded_value_test() {
+        let allocation = RibosomeEncodedAllocation::from(u64_merge_bits(1, 2));
+        for (ribosome_encoded_value, expected) in vec![
+            (Success, Err(HolochainError::Ribosome(ZeroSizedAllocation))),
+            (
+                Failure(CallbackFailed),
+                Err(HolochainError::Ribosome(CallbackFailed)),
+            ),
+            (Allocation(allocation.clone()), Ok(allocation)),
+        ] {
+            let result: Result<RibosomeEncodedAllocation, HolochainError> =
+                ribosome_encoded_value.into();
+            assert_eq!(expected, result);
+        }
+    }
+
+    #[test]
+    fn ribosome_encoded_allocation_try_new_test() {
+        // error shaped
+        assert_eq!(
+            Err(NotAnAllocation),
+            RibosomeEncodedAllocation::try_new(RibosomeEncodingBits::from(Failure(OutOfMemory))),
+        );
+        assert_eq!(Err(NotAnAllocation), RibosomeEncodedAllocation::try_new(0));
+
+        assert_eq!(
+            Err(OutOfMemory),
+            RibosomeEncodedAllocation::try_new(u64_merge_bits(std::u32::MAX, 1)),
+        );
+
+        let bits = u64_merge_bits(4, 8);
+        assert_eq!(
+            Ok(RibosomeEncodedAllocation::from(bits)),
+            RibosomeEncodedAllocation::try_new(bits),
+        );
+        assert_eq!(
+            Ok(RibosomeEncodedAllocation::from(u64_merge_bits(
+                std::u32::MAX - 1,
+                1
+            ))),
+            RibosomeEncodedAllocation::try_new(u64_merge_bits(std::u32::MAX - 1, 1)),
+        );
+    }
+
+    #[test]
+    fn ribosome_encoded_allocation_format_test() {
+        let allocation = RibosomeEncodedAllocation::from(u64_merge_bits(0b1010, 0b1111));
+
+        assert_eq!(
+            "101000000000000000000000000000001111",
+            format!("{:b}", allocation),
+        );
+        assert_eq!(
+            "0b0000000000000000000000000000101000000000000000000000000000001111",
+            format!("{:#066b}", allocation),
+        );
+        assert_eq!("a0000000f", format!("{:x}", allocation));
+        assert_eq!("0x0000000a0000000f", format!("{:#018x}", allocation));
+        assert_eq!("A0000000F", format!("{:X}", allocation));
+    }
+
+    #[test]
+    fn ribosome_encoded_allocation_offset_length_test() {
+        let allocation = RibosomeEncodedAllocation::from(u64_merge_bits(10, 15));
+        assert_eq!(10, allocation.offset());
+        assert_eq!(15, allocation.length());
+
+        // no validation, the halves are returned as encoded
+        let allocation = RibosomeEncodedAllocation::from(u64_merge_bits(std::u32::MAX, 0));
+        assert_eq!(std::u32::MAX, allocation.offset());
+        assert_eq!(0, allocation.length());
+    }
+
+    #[test]
+    fn ribosome_encoded_allocation_debug_test() {
+        let allocation = RibosomeEncodedAllocation::from(u64_merge_bits(10, 15));
+
+        assert_eq!(
+            "RibosomeEncodedAllocation { bits: 0x0000000a0000000f, offset: 10, length: 15 }",
+            format!("{:?}", allocation),
+        );
+        assert_eq!(
+            "Allocation(RibosomeEncodedAllocation { bits: 0x0000000a0000000f, offset: 10, length: 15 })",
+            format!("{:?}", Allocation(allocation)),
+        );
+    }
+
+    #[test]
+    fn severity_test() {
+        assert_eq!(
+            RibosomeErrorSeverity::Recoverable,
+            UnknownEntryType.severity()
+        );
+        assert_eq!(RibosomeErrorSeverity::Fatal, OutOfMemory.severity());
+        assert!(RibosomeErrorSeverity::Fatal > RibosomeErrorSeverity::Recoverable);
+    }
+
+    #[test]
+    fn merge_test() {
+        // the fatal error wins whichever side it is on
+        assert_eq!(OutOfMemory, UnknownEntryType.merge(OutOfMemory));
+        assert_eq!(OutOfMemory, OutOfMemory.merge(UnknownEntryType));
+
+        // ties favour the first error
+        assert_eq!(
+            UnknownEntryType,
+            UnknownEntryType.merge(RecursiveCallForbidden)
+        );
+        assert_eq!(NotAnAllocation, NotAnAllocation.merge(OutOfMemory));
+        assert_eq!(
+            CallbackFailed,
+            CallbackFailed.merge(ArgumentDeserializationFailed)
+        );
+    }
+
+    #[test]
+    fn ribosome_error_code_ord_test() {
+        assert!(Unspecified < ArgumentDeserializationFailed);
+        assert!(UnknownEntryType > ZeroSizedAllocation);
+        assert_eq!(Ordering::Equal, OutOfMemory.cmp(&OutOfMemory));
+
+        let mut errors = vec![
+            UnknownEntryType,
+            OutOfMemory,
+            Unspecified,
+            CallbackFailed,
+            OutOfMemory,
+        ];
+        errors.sort();
+        assert_eq!(
+            vec![
+                Unspecified,
+                OutOfMemory,
+                OutOfMemory,
+                CallbackFailed,
+                UnknownEntryType
+            ],
+            errors,
+        );
+
+        // any two codes compare the same as their discriminants
+        let mut discriminants: Vec<RibosomeEncodingBits> = errors
+            .iter()
+            .map(|code| code.clone() as RibosomeEncodingBits)
+            .collect();
+        discriminants.sort();
+        assert_eq!(
+            discriminants,
+            errors
+                .into_iter()
+                .map(|code| code as RibosomeEncodingBits)
+                .collect::<Vec<RibosomeEncodingBits>>(),
+        );
+    }
+
+    #[test]
+    fn documented_codes_test() {
+        assert_eq!(12, DOCUMENTED_CODES.len());
+        for (name, code, description) in DOCUMENTED_CODES {
+            let error_code = RibosomeErrorCode::from_code_bits(*code).unwrap();
+            assert_eq!(*name, format!("{:?}", error_code));
+            assert_eq!(*description, error_code.as_str());
+        }
+    }
+
+    #[test]
+    fn codes_test() {
+        for (code, error_code) in vec![
+            (codes::UNSPECIFIED, Unspecified),
+            (
+                codes::ARGUMENT_DESERIALIZATION_FAILED,
+                ArgumentDeserializationFailed,
+            ),
+            (codes::OUT_OF_MEMORY, OutOfMemory),
+            (
+                codes::RECEIVED_WRONG_ACTION_RESULT,
+                ReceivedWrongActionResult,
+            ),
+            (codes::CALLBACK_FAILED, CallbackFailed),
+            (codes::RECURSIVE_CALL_FORBIDDEN, RecursiveCallForbidden),
+            (
+                codes::RESPONSE_SERIALIZATION_FAILED,
+                ResponseSerializationFailed,
+            ),
+            (codes::NOT_AN_ALLOCATION, NotAnAllocation),
+            (codes::ZERO_SIZED_ALLOCATION, ZeroSizedAllocation),
+            (codes::UNKNOWN_ENTRY_TYPE, UnknownEntryType),
+            (codes::IO_ERROR, IoError),
+            (codes::NETWORK_ERROR, NetworkError),
+        ] {
+            assert_eq!(code, error_code.clone() as RibosomeEncodingBits);
+            assert_eq!(
+                code,
+                RibosomeEncodingBits::from(Failure(error_code.clone()))
+            );
+            assert_eq!(
+                Some(error_code),
+                RibosomeErrorCode::from_encoding_bits(code)
+            );
+        }
+    }
+
+    #[test]
+    fn from_encoding_bits_test() {
+        assert_eq!(
+            Some(OutOfMemory),
+            RibosomeErrorCode::from_encoding_bits(OutOfMemory as RibosomeEncodingBits),
+        );
+        assert_eq!(
+            Some(UnknownEntryType),
+            RibosomeErrorCode::from_encoding_bits(10 << 32),
+        );
+        assert_eq!(
+            Some(Unspecified),
+            RibosomeErrorCode::from_encoding_bits(1 << 32),
+        );
+        assert_eq!(
+            Some(NetworkError),
+            RibosomeErrorCode::from_encoding_bits(16 << 32),
+        );
+        // reserved gap before the networking codes
+        assert_eq!(None, RibosomeErrorCode::from_encoding_bits(11 << 32));
+
+        assert_eq!(None, RibosomeErrorCode::from_encoding_bits(0));
+        // unknown code
+        assert_eq!(None, RibosomeErrorCode::from_encoding_bits(99 << 32));
+        // allocation
+        assert_eq!(
+            None,
+            RibosomeErrorCode::from_encoding_bits(u64_merge_bits(3, 1))
+        );
+        // unshifted
+        assert_eq!(None, RibosomeErrorCode::from_encoding_bits(3));
+    }
+
+    #[test]
+    fn to_and_from_u64_test() {
+        assert_eq!(3 << 32, OutOfMemory.to_u64());
+        for (_, code, _) in DOCUMENTED_CODES {
+            let error_code = RibosomeErrorCode::from_code_bits(*code).unwrap();
+            assert_eq!(u64_merge_bits(*code, 0), error_code.to_u64());
+            assert_eq!(
+                Ok(error_code.clone()),
+                RibosomeErrorCode::from_u64(error_code.to_u64())
+            );
+        }
+
+        assert_eq!(
+            Err(HolochainError::ErrorGeneric(
+                "0x3 is not a RibosomeErrorCode".to_string()
+            )),
+            RibosomeErrorCode::from_u64(3),
+        );
+        assert!(RibosomeErrorCode::from_u64(0).is_err());
+        assert!(RibosomeErrorCode::from_u64(u64_merge_bits(11, 0)).is_err());
+    }
+
+    #[test]
+    fn from_code_bits_test() {
+        assert_eq!(Ok(OutOfMemory), RibosomeErrorCode::from_code_bits(3));
+        assert_eq!(
+            Err(HolochainError::ErrorGeneric(
+                "0 is not a RibosomeErrorCode code".to_string()
+            )),
+            RibosomeErrorCode::from_code_bits(0),
+        );
+        assert!(RibosomeErrorCode::from_code_bits(11).is_err());
+        // shifted
+        assert!(RibosomeErrorCode::from_code_bits(std::u32::MAX).is_err());
+    }
+
     #[test]
     #[should_panic]
+    #[allow(deprecated)]
     fn code_zero() {
         RibosomeErrorCode::from_code_int(0);
     }
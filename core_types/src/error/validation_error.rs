@@ -0,0 +1,114 @@
+use crate::json::JsonString;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::{error::Error, fmt};
+
+/// Structured details of a failed validation
+/// e.g. so that the RPC layer can tell a client which field failed and why
+#[derive(Clone, Debug, PartialEq, Hash, Eq, Serialize, Deserialize, PartialOrd, Ord)]
+pub struct ValidationError {
+    pub field: String,
+    pub message: String,
+    /// the value that failed validation, if it is known
+    #[serde(with = "optional_json_string")]
+    pub value: Option<JsonString>,
+}
+
+impl ValidationError {
+    pub fn new(field: &str, message: &str, value: Option<JsonString>) -> ValidationError {
+        ValidationError {
+            field: field.to_string(),
+            message: message.to_string(),
+            value,
+        }
+    }
+}
+
+/// (field, message) without a value, for call sites that only have strings to hand
+impl<'a> From<(&'a str, &'a str)> for ValidationError {
+    fn from((field, message): (&str, &str)) -> Self {
+        ValidationError::new(field, message, None)
+    }
+}
+
+impl Error for ValidationError {}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.value {
+            Some(value) => write!(f, "{}: {} (got {})", self.field, self.message, value),
+            None => write!(f, "{}: {}", self.field, self.message),
+        }
+    }
+}
+
+/// JsonString must not itself be serialized so the value is carried as its raw JSON string
+mod optional_json_string {
+    use super::*;
+
+    pub fn serialize<S>(value: &Option<JsonString>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        value.as_ref().map(String::from).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Option<JsonString>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Ok(Option::<String>::deserialize(deserializer)?.map(JsonString::from))
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::error::HolochainError;
+    use serde_json;
+
+    #[test]
+    fn validation_error_from_tuple_test() {
+        assert_eq!(
+            ValidationError {
+                field: "title".to_string(),
+                message: "is too long".to_string(),
+                value: None,
+            },
+            ValidationError::from(("title", "is too long")),
+        );
+    }
+
+    #[test]
+    fn validation_error_display_test() {
+        assert_eq!(
+            "title: is too long",
+            ValidationError::from(("title", "is too long")).to_string(),
+        );
+        assert_eq!(
+            "title: is too long (got \"foo\")",
+            ValidationError::new("title", "is too long", Some(JsonString::from("\"foo\"")))
+                .to_string(),
+        );
+    }
+
+    #[test]
+    fn validation_error_serialization_round_trip_test() {
+        let validation_error =
+            ValidationError::new("count", "must be positive", Some(JsonString::from("-1")));
+        let serialized = serde_json::to_string(&validation_error).unwrap();
+        assert_eq!(
+            "{\"field\":\"count\",\"message\":\"must be positive\",\"value\":\"-1\"}",
+            serialized,
+        );
+        assert_eq!(
+            validation_error,
+            serde_json::from_str::<ValidationError>(&serialized).unwrap(),
+        );
+
+        let holochain_error = HolochainError::from(validation_error);
+        assert_eq!(
+            holochain_error,
+            serde_json::from_str(&serde_json::to_string(&holochain_error).unwrap()).unwrap(),
+        );
+    }
+}
@@ -2,11 +2,15 @@
 //! to enforce a standardized serialization of data to/from json.
 
 use crate::error::{HcResult, HolochainError};
-use serde::{de::DeserializeOwned, Serialize};
+use serde::{
+    de::{DeserializeOwned, IgnoredAny, MapAccess, Visitor},
+    Deserializer, Serialize,
+};
 use serde_json;
 use std::{
     convert::TryFrom,
     fmt::{Debug, Display, Formatter, Result as FmtResult},
+    marker::PhantomData,
 };
 
 /// track json serialization with the rust type system!
@@ -17,7 +21,7 @@ use std::{
 /// instead, implement and use the native `From` trait to move between types
 /// - moving to/from String, str, JsonString and JsonString simply (un)wraps it as raw JSON data
 /// - moving to/from any other type must offer a reliable serialization/deserialization strategy
-#[derive(Debug, PartialEq, Clone, Hash, Eq)]
+#[derive(Debug, PartialEq, Clone, Hash, Eq, PartialOrd, Ord)]
 pub struct JsonString(String);
 
 impl JsonString {
@@ -31,14 +35,330 @@ impl JsonString {
         JsonString::from("{}")
     }
 
+    /// a JSON array of the items in order, built directly from their strings
+    /// e.g. for zome functions returning lists without collecting a Vec<T> to serialize first
+    /// the items are trusted to be valid JSON as they are not parsed
+    pub fn array<I: IntoIterator<Item = JsonString>>(items: I) -> JsonString {
+        let mut array = String::from("[");
+        for (i, item) in items.into_iter().enumerate() {
+            if i > 0 {
+                array.push(',');
+            }
+            array.push_str(&item.0);
+        }
+        array.push(']');
+        JsonString::from(array)
+    }
+
+    /// a builder writing a JSON object or array straight into one string of capacity bytes
+    /// @see JsonStringBuilder
+    pub fn with_capacity<K: JsonBuilderKind>(hint: usize) -> JsonStringBuilder<K> {
+        JsonStringBuilder::new(hint)
+    }
+
     pub fn is_null(&self) -> bool {
         self == &Self::null()
     }
 
+    /// the first non-whitespace byte, which is enough to tell the type of valid JSON
+    fn first_byte(&self) -> Option<u8> {
+        self.0.bytes().find(|b| !b.is_ascii_whitespace())
+    }
+
+    // cheap checks of the top level JSON type without parsing or allocating
+    // only the first byte is inspected so the JSON is not validated
+
+    pub fn is_object(&self) -> bool {
+        self.first_byte() == Some(b'{')
+    }
+
+    pub fn is_array(&self) -> bool {
+        self.first_byte() == Some(b'[')
+    }
+
+    pub fn is_string(&self) -> bool {
+        self.first_byte() == Some(b'"')
+    }
+
+    pub fn is_number(&self) -> bool {
+        match self.first_byte() {
+            Some(b'-') => true,
+            Some(b) => b.is_ascii_digit(),
+            None => false,
+        }
+    }
+
+    pub fn is_boolean(&self) -> bool {
+        match self.first_byte() {
+            Some(b't') | Some(b'f') => true,
+            _ => false,
+        }
+    }
+
     /// achieves the same outcome as serde_json::to_vec()
     pub fn into_bytes(&self) -> Vec<u8> {
         self.0.to_owned().into_bytes()
     }
+
+    /// compact, key sorted JSON for the same value
+    /// e.g. pretty printed JSON normalizes to the same bytes as the compact original
+    pub fn normalize(self) -> Result<JsonString, HolochainError> {
+        let value: serde_json::Value = serde_json::from_str(&self.0)?;
+        Ok(JsonString::from(sorted_json_value(value)))
+    }
+
+    /// true if both JsonStrings normalize to the same value
+    pub fn eq_semantic(&self, other: &JsonString) -> Result<bool, HolochainError> {
+        Ok(self.clone().normalize()? == other.clone().normalize()?)
+    }
+
+    /// the keys of a top level JSON object in order, without deserializing the values
+    /// SerializationError if the JSON is not an object
+    pub fn keys(&self) -> Result<Vec<String>, HolochainError> {
+        let mut deserializer = serde_json::Deserializer::from_str(&self.0);
+        let keys = deserializer.deserialize_map(KeysVisitor)?;
+        deserializer.end()?;
+        Ok(keys)
+    }
+
+    /// the value of a top level field of a JSON object as its own JsonString
+    /// e.g. the "type" of an entry without deserializing to the entry struct
+    pub fn get_field(&self, key: &str) -> Result<JsonString, HolochainError> {
+        let mut value: serde_json::Value = serde_json::from_str(&self.0)?;
+        value
+            .as_object_mut()
+            .and_then(|object| object.remove(key))
+            .map(JsonString::from)
+            .ok_or_else(|| {
+                HolochainError::SerializationError(format!("missing field `{}` in {}", key, self))
+            })
+    }
+
+    /// every field level difference from a to b, empty if they are the same value
+    /// objects are compared key by key and arrays index by index, any other differing values
+    /// are Changed as a whole
+    /// e.g. to point test assertion failures at the field that changed in a large JSON value
+    pub fn diff(a: &JsonString, b: &JsonString) -> Result<Vec<JsonDiff>, HolochainError> {
+        let a: serde_json::Value = serde_json::from_str(&a.0)?;
+        let b: serde_json::Value = serde_json::from_str(&b.0)?;
+        let mut diffs = Vec::new();
+        diff_json_values("", &a, &b, &mut diffs);
+        Ok(diffs)
+    }
+}
+
+/// incrementally builds a JSON object from fields or a JSON array from items
+/// e.g. for large zome API responses without collecting and serializing a Vec first
+/// K is JsonObject or JsonArray, so fields and array items can't be mixed, K is usually
+/// inferred from the first insert, otherwise use object() or array()
+/// keys are escaped but values are trusted to be valid JSON as they are not parsed
+#[derive(Clone, Debug)]
+pub struct JsonStringBuilder<K: JsonBuilderKind> {
+    json: String,
+    empty: bool,
+    kind: PhantomData<K>,
+}
+
+/// the kind of JSON value a JsonStringBuilder builds, @see JsonObject and JsonArray
+pub trait JsonBuilderKind {
+    const OPEN: char;
+    const CLOSE: char;
+}
+
+/// a JsonStringBuilder of fields
+#[derive(Clone, Debug)]
+pub enum JsonObject {}
+
+impl JsonBuilderKind for JsonObject {
+    const OPEN: char = '{';
+    const CLOSE: char = '}';
+}
+
+/// a JsonStringBuilder of array items
+#[derive(Clone, Debug)]
+pub enum JsonArray {}
+
+impl JsonBuilderKind for JsonArray {
+    const OPEN: char = '[';
+    const CLOSE: char = ']';
+}
+
+impl<K: JsonBuilderKind> JsonStringBuilder<K> {
+    pub fn new(capacity: usize) -> Self {
+        let mut json = String::with_capacity(capacity);
+        json.push(K::OPEN);
+        JsonStringBuilder {
+            json,
+            empty: true,
+            kind: PhantomData,
+        }
+    }
+
+    /// separates the next entry from the previous one, if any
+    fn separate(&mut self) {
+        if self.empty {
+            self.empty = false;
+        } else {
+            self.json.push(',');
+        }
+    }
+
+    /// the built JSON, an empty builder is an empty object or array
+    pub fn finish(mut self) -> JsonString {
+        self.json.push(K::CLOSE);
+        JsonString::from(self.json)
+    }
+}
+
+impl JsonStringBuilder<JsonObject> {
+    pub fn object(capacity: usize) -> Self {
+        JsonStringBuilder::new(capacity)
+    }
+
+    pub fn insert_field(&mut self, key: &str, value: JsonString) -> &mut Self {
+        self.separate();
+        // serializing a str can't fail
+        let key = serde_json::to_string(key).expect("could not Jsonify key");
+        self.json.push_str(&key);
+        self.json.push(':');
+        self.json.push_str(&value.0);
+        self
+    }
+}
+
+impl JsonStringBuilder<JsonArray> {
+    pub fn array(capacity: usize) -> Self {
+        JsonStringBuilder::new(capacity)
+    }
+
+    pub fn append_array_item(&mut self, item: JsonString) -> &mut Self {
+        self.separate();
+        self.json.push_str(&item.0);
+        self
+    }
+}
+
+/// a single field level difference between two JSON values, @see JsonString::diff
+/// path is a JSON pointer (RFC 6901) to the field, e.g. "/body/tags/0", "" for the whole value
+#[derive(Debug, Clone, PartialEq)]
+pub enum JsonDiff {
+    Added {
+        path: String,
+        value: JsonString,
+    },
+    Removed {
+        path: String,
+        value: JsonString,
+    },
+    Changed {
+        path: String,
+        old: JsonString,
+        new: JsonString,
+    },
+}
+
+fn diff_json_values(
+    path: &str,
+    a: &serde_json::Value,
+    b: &serde_json::Value,
+    diffs: &mut Vec<JsonDiff>,
+) {
+    match (a, b) {
+        (serde_json::Value::Object(a_map), serde_json::Value::Object(b_map)) => {
+            for (key, a_value) in a_map {
+                let key_path = json_pointer(path, key);
+                match b_map.get(key) {
+                    Some(b_value) => diff_json_values(&key_path, a_value, b_value, diffs),
+                    None => diffs.push(JsonDiff::Removed {
+                        path: key_path,
+                        value: JsonString::from(a_value.clone()),
+                    }),
+                }
+            }
+            for (key, b_value) in b_map {
+                if !a_map.contains_key(key) {
+                    diffs.push(JsonDiff::Added {
+                        path: json_pointer(path, key),
+                        value: JsonString::from(b_value.clone()),
+                    });
+                }
+            }
+        }
+        (serde_json::Value::Array(a_values), serde_json::Value::Array(b_values)) => {
+            for i in 0..std::cmp::max(a_values.len(), b_values.len()) {
+                let index_path = json_pointer(path, &i.to_string());
+                match (a_values.get(i), b_values.get(i)) {
+                    (Some(a_value), Some(b_value)) => {
+                        diff_json_values(&index_path, a_value, b_value, diffs)
+                    }
+                    (Some(a_value), None) => diffs.push(JsonDiff::Removed {
+                        path: index_path,
+                        value: JsonString::from(a_value.clone()),
+                    }),
+                    (None, Some(b_value)) => diffs.push(JsonDiff::Added {
+                        path: index_path,
+                        value: JsonString::from(b_value.clone()),
+                    }),
+                    (None, None) => unreachable!(),
+                }
+            }
+        }
+        _ => {
+            if a != b {
+                diffs.push(JsonDiff::Changed {
+                    path: path.to_string(),
+                    old: JsonString::from(a.clone()),
+                    new: JsonString::from(b.clone()),
+                });
+            }
+        }
+    }
+}
+
+/// appends a reference token to a JSON pointer, escaping ~ and / as RFC 6901 requires
+fn json_pointer(path: &str, token: &str) -> String {
+    format!("{}/{}", path, token.replace('~', "~0").replace('/', "~1"))
+}
+
+/// collects the keys of a map and skips over the values
+struct KeysVisitor;
+
+impl<'de> Visitor<'de> for KeysVisitor {
+    type Value = Vec<String>;
+
+    fn expecting(&self, formatter: &mut Formatter) -> FmtResult {
+        formatter.write_str("a JSON object")
+    }
+
+    fn visit_map<M: MapAccess<'de>>(self, mut map: M) -> Result<Self::Value, M::Error> {
+        let mut keys = Vec::new();
+        while let Some(key) = map.next_key::<String>()? {
+            map.next_value::<IgnoredAny>()?;
+            keys.push(key);
+        }
+        Ok(keys)
+    }
+}
+
+/// rebuilds a value with the keys of every object in sorted order
+/// serde_json preserves insertion order so the keys must be inserted sorted
+fn sorted_json_value(value: serde_json::Value) -> serde_json::Value {
+    match value {
+        serde_json::Value::Object(map) => {
+            let mut entries: Vec<(String, serde_json::Value)> = map.into_iter().collect();
+            entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+            serde_json::Value::Object(
+                entries
+                    .into_iter()
+                    .map(|(k, v)| (k, sorted_json_value(v)))
+                    .collect(),
+            )
+        }
+        serde_json::Value::Array(values) => {
+            serde_json::Value::Array(values.into_iter().map(sorted_json_value).collect())
+        }
+        other => other,
+    }
 }
 
 impl From<String> for JsonString {
@@ -295,7 +615,7 @@ impl TryFrom<JsonString> for RawString {
 pub mod tests {
     use crate::{
         error::HolochainError,
-        json::{JsonString, RawString},
+        json::{JsonDiff, JsonString, JsonStringBuilder, RawString},
     };
     use serde_json;
     use std::convert::TryFrom;
@@ -305,6 +625,48 @@ pub mod tests {
         foo: String,
     }
 
+    #[test]
+    fn json_string_builder_object_test() {
+        let mut builder = JsonString::with_capacity(64);
+        builder
+            .insert_field("foo", JsonString::from(RawString::from("bar")))
+            .insert_field("quo\"te", JsonString::from(1_u32));
+        builder.insert_field("empty", JsonString::empty_object());
+        assert_eq!(
+            JsonString::from("{\"foo\":\"bar\",\"quo\\\"te\":1,\"empty\":{}}"),
+            builder.finish(),
+        );
+
+        assert_eq!(
+            JsonString::empty_object(),
+            JsonStringBuilder::object(0).finish()
+        );
+    }
+
+    #[test]
+    fn json_string_builder_array_test() {
+        let mut builder = JsonStringBuilder::array(16);
+        builder
+            .append_array_item(JsonString::from(1_u32))
+            .append_array_item(JsonString::null())
+            .append_array_item(JsonString::from(RawString::from("foo")));
+        let json = builder.finish();
+        assert_eq!(JsonString::from("[1,null,\"foo\"]"), json);
+        assert_eq!(
+            JsonString::array(vec![
+                JsonString::from(1_u32),
+                JsonString::null(),
+                JsonString::from(RawString::from("foo")),
+            ]),
+            json,
+        );
+
+        assert_eq!(
+            JsonString::array(vec![]),
+            JsonStringBuilder::array(0).finish()
+        );
+    }
+
     #[test]
     fn default_json_round_trip_test() {
         let test = DeriveTest { foo: "bar".into() };
@@ -324,11 +686,232 @@ pub mod tests {
         assert_eq!(String::from("null"), String::from(JsonString::null()),);
     }
 
+    #[test]
+    fn json_array_test() {
+        assert_eq!(JsonString::from("[]"), JsonString::array(vec![]));
+        assert_eq!(
+            JsonString::from("[\"foo\"]"),
+            JsonString::array(vec![JsonString::from("\"foo\"")]),
+        );
+        assert_eq!(
+            JsonString::from("[1,\"foo\",{\"bar\":null},[]]"),
+            JsonString::array(vec![
+                JsonString::from("1"),
+                JsonString::from("\"foo\""),
+                JsonString::from("{\"bar\":null}"),
+                JsonString::array(vec![]),
+            ]),
+        );
+
+        // any iterator, not just a Vec
+        assert_eq!(
+            JsonString::from("[null,null]"),
+            JsonString::array((0..2).map(|_| JsonString::null())),
+        );
+    }
+
     #[test]
     fn json_into_bytes_test() {
         assert_eq!(JsonString::from("foo").into_bytes(), vec![102, 111, 111],);
     }
 
+    #[test]
+    fn json_normalize_test() {
+        assert_eq!(
+            Ok(JsonString::from(
+                "{\"a\":[1,{\"b\":null,\"c\":true}],\"d\":\"e\"}"
+            )),
+            JsonString::from(
+                "{ \"d\": \"e\",\n  \"a\": [\n    1,\n    { \"c\": true, \"b\": null }\n  ]\n}"
+            )
+            .normalize(),
+        );
+
+        assert!(JsonString::from("{\"a\":").normalize().is_err());
+    }
+
+    #[test]
+    fn json_type_predicates_test() {
+        let object = JsonString::from(" {\"a\":1}");
+        let array = JsonString::from("\n[1,2]");
+        let string = JsonString::from("\"foo\"");
+        let numbers = vec![JsonString::from("-1"), JsonString::from("0.5")];
+        let booleans = vec![JsonString::from("true"), JsonString::from("\tfalse")];
+        let empty = JsonString::from(" ");
+
+        assert!(object.is_object());
+        assert!(array.is_array());
+        assert!(string.is_string());
+        for number in &numbers {
+            assert!(number.is_number());
+        }
+        for boolean in &booleans {
+            assert!(boolean.is_boolean());
+        }
+
+        for not_object in vec![&array, &string, &numbers[0], &booleans[0], &empty] {
+            assert!(!not_object.is_object());
+        }
+        for not_array in vec![&object, &string, &numbers[1], &booleans[1], &empty] {
+            assert!(!not_array.is_array());
+        }
+        for json in vec![&object, &array, &numbers[0], &booleans[0], &empty] {
+            assert!(!json.is_string());
+        }
+        for json in vec![&object, &array, &string, &booleans[0], &empty] {
+            assert!(!json.is_number());
+        }
+        for json in vec![&object, &array, &string, &numbers[0], &empty] {
+            assert!(!json.is_boolean());
+        }
+        for json in vec![&JsonString::null(), &empty] {
+            assert!(!json.is_number());
+            assert!(!json.is_boolean());
+        }
+    }
+
+    #[test]
+    fn json_diff_test() {
+        let a = JsonString::from(
+            "{\"type\":\"post\",\"body\":{\"title\":\"foo\",\"tags\":[1,2]},\"old\":true}",
+        );
+
+        // the same value in a different key order has no differences
+        assert_eq!(
+            Ok(vec![]),
+            JsonString::diff(
+                &a,
+                &JsonString::from(
+                    "{\"old\":true,\"body\":{\"tags\":[1,2],\"title\":\"foo\"},\"type\":\"post\"}"
+                ),
+            ),
+        );
+
+        let b = JsonString::from(
+            "{\"type\":\"post\",\"body\":{\"title\":\"bar\",\"tags\":[1,3,4]},\"new\":null}",
+        );
+        assert_eq!(
+            Ok(vec![
+                JsonDiff::Changed {
+                    path: "/body/title".to_string(),
+                    old: JsonString::from("\"foo\""),
+                    new: JsonString::from("\"bar\""),
+                },
+                JsonDiff::Changed {
+                    path: "/body/tags/1".to_string(),
+                    old: JsonString::from("2"),
+                    new: JsonString::from("3"),
+                },
+                JsonDiff::Added {
+                    path: "/body/tags/2".to_string(),
+                    value: JsonString::from("4"),
+                },
+                JsonDiff::Removed {
+                    path: "/old".to_string(),
+                    value: JsonString::from("true"),
+                },
+                JsonDiff::Added {
+                    path: "/new".to_string(),
+                    value: JsonString::null(),
+                },
+            ]),
+            JsonString::diff(&a, &b),
+        );
+
+        // a shorter array removes the trailing items
+        assert_eq!(
+            Ok(vec![JsonDiff::Removed {
+                path: "/1".to_string(),
+                value: JsonString::from("2"),
+            }]),
+            JsonString::diff(&JsonString::from("[1,2]"), &JsonString::from("[1]")),
+        );
+
+        // values of different types change as a whole, the root path is empty
+        assert_eq!(
+            Ok(vec![JsonDiff::Changed {
+                path: "".to_string(),
+                old: JsonString::from("[1]"),
+                new: JsonString::from("{\"a\":1}"),
+            }]),
+            JsonString::diff(&JsonString::from("[1]"), &JsonString::from("{\"a\":1}")),
+        );
+
+        // ~ and / in keys are escaped in the path
+        assert_eq!(
+            Ok(vec![JsonDiff::Changed {
+                path: "/a~1b~0c".to_string(),
+                old: JsonString::from("1"),
+                new: JsonString::from("2"),
+            }]),
+            JsonString::diff(
+                &JsonString::from("{\"a/b~c\":1}"),
+                &JsonString::from("{\"a/b~c\":2}"),
+            ),
+        );
+
+        assert!(
+            JsonString::diff(&JsonString::from("{"), &JsonString::from("{}"))
+                .unwrap_err()
+                .is_serialization_error()
+        );
+    }
+
+    #[test]
+    fn json_keys_test() {
+        assert_eq!(
+            Ok(vec!["b".to_string(), "a".to_string(), "c".to_string()]),
+            JsonString::from("{\"b\":{\"nested\":1},\"a\":[1,2],\"c\":null}").keys(),
+        );
+        assert_eq!(Ok(vec![]), JsonString::empty_object().keys());
+
+        assert!(JsonString::from("[1,2]")
+            .keys()
+            .unwrap_err()
+            .is_serialization_error());
+        assert!(JsonString::from("\"foo\"").keys().is_err());
+        assert!(JsonString::from("{\"a\":").keys().is_err());
+        assert!(JsonString::from("{} {}").keys().is_err());
+    }
+
+    #[test]
+    fn json_get_field_test() {
+        let json =
+            JsonString::from("{\"type\":\"post\",\"body\":{\"title\":\"foo\",\"tags\":[1,2]}}");
+
+        assert_eq!(Ok(JsonString::from("\"post\"")), json.get_field("type"));
+        assert_eq!(
+            Ok(JsonString::from("{\"title\":\"foo\",\"tags\":[1,2]}")),
+            json.get_field("body"),
+        );
+        assert_eq!(
+            Err(HolochainError::SerializationError(
+                "missing field `title` in {\"type\":\"post\",\"body\":{\"title\":\"foo\",\"tags\":[1,2]}}"
+                    .to_string()
+            )),
+            json.get_field("title"),
+        );
+        // not an object
+        assert!(JsonString::from("[1,2]").get_field("type").is_err());
+        assert!(JsonString::from("{\"type\":").get_field("type").is_err());
+    }
+
+    #[test]
+    fn json_eq_semantic_test() {
+        let compact = JsonString::from("{\"foo\":\"bar\",\"baz\":[1,2]}");
+        let pretty = JsonString::from("{\n  \"baz\": [1, 2],\n  \"foo\": \"bar\"\n}");
+        assert_ne!(compact, pretty);
+        assert_eq!(Ok(true), compact.eq_semantic(&pretty));
+
+        // array order is significant
+        assert_eq!(
+            Ok(false),
+            compact.eq_semantic(&JsonString::from("{\"foo\":\"bar\",\"baz\":[2,1]}")),
+        );
+
+        assert!(compact.eq_semantic(&JsonString::from("not json")).is_err());
+    }
+
     #[test]
     fn json_result_round_trip_test() {
         let result: Result<String, HolochainError> =
@@ -39,8 +39,9 @@ impl From<HolochainError> for ZomeApiError {
     fn from(holochain_error: HolochainError) -> Self {
         match holochain_error {
             HolochainError::ValidationFailed(s) => ZomeApiError::ValidationFailed(s),
+            HolochainError::Validation(e) => ZomeApiError::ValidationFailed(e.to_string()),
             HolochainError::Timeout => ZomeApiError::Timeout,
-            _ => ZomeApiError::Internal(holochain_error.to_string().into()),
+            _ => ZomeApiError::Internal(holochain_error.message()),
         }
     }
 }
@@ -59,7 +60,7 @@ impl From<String> for ZomeApiError {
 
 impl From<RibosomeErrorCode> for ZomeApiError {
     fn from(ribosome_error_code: RibosomeErrorCode) -> ZomeApiError {
-        ZomeApiError::from(ribosome_error_code.to_string())
+        ZomeApiError::from(ribosome_error_code.as_str().to_string())
     }
 }
 
@@ -76,6 +77,15 @@ impl From<AllocationError> for ZomeApiError {
             AllocationError::Serialization => {
                 ZomeApiError::Internal("Allocation serialization failure".into())
             }
+            AllocationError::StackOverflow { .. } => {
+                ZomeApiError::Internal("Allocation overflows available memory".into())
+            }
+            AllocationError::ReservedRegion => {
+                ZomeApiError::Internal("Allocation intrudes on reserved memory".into())
+            }
+            AllocationError::Corrupt { reason } => {
+                ZomeApiError::Internal(format!("Memory corruption detected: {}", reason))
+            }
         }
     }
 }
@@ -100,7 +110,7 @@ pub type ZomeApiResult<T> = Result<T, ZomeApiError>;
 mod tests {
 
     use error::{ZomeApiError, ZomeApiResult};
-    use holochain_core_types::json::JsonString;
+    use holochain_core_types::{error::HolochainError, json::JsonString};
 
     #[test]
     fn zome_api_result_json_result_round_trip_test() {
@@ -111,4 +121,17 @@ mod tests {
             JsonString::from("{\"Err\":\"FunctionNotImplemented\"}"),
         );
     }
+
+    #[test]
+    fn zome_api_error_from_holochain_error_test() {
+        // Internal carries the message without the [Variant] prefix of Display
+        assert_eq!(
+            ZomeApiError::Internal(String::from("foo")),
+            ZomeApiError::from(HolochainError::IoError(String::from("foo"))),
+        );
+        assert_eq!(
+            ZomeApiError::Timeout,
+            ZomeApiError::from(HolochainError::Timeout),
+        );
+    }
 }
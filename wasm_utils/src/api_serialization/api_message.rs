@@ -0,0 +1,143 @@
+use holochain_core_types::{
+    error::{HolochainError, RibosomeEncodedValue},
+    json::{default_to_json, default_try_from_json, JsonString},
+};
+use memory::{read::read_json, ribosome::IntoAllocationResult};
+use serde::{de::DeserializeOwned, Serialize};
+use std::{convert::TryFrom, fmt::Debug};
+
+/// arguments of a zome API call, tagged with their type on both sides of the WASM boundary
+/// any T that serializes moves to/from JsonString without its own DefaultJson
+#[derive(Clone, Debug, PartialEq)]
+pub struct ApiRequest<T>(pub T);
+
+/// return value of a zome API call, @see ApiRequest
+/// ApiResponse<JsonString> is the untyped response as read from WASM memory
+#[derive(Clone, Debug, PartialEq)]
+pub struct ApiResponse<T>(pub T);
+
+impl<T: Serialize + Debug> From<ApiRequest<T>> for JsonString {
+    fn from(request: ApiRequest<T>) -> JsonString {
+        default_to_json(request.0)
+    }
+}
+
+impl<T: DeserializeOwned> TryFrom<JsonString> for ApiRequest<T> {
+    type Error = HolochainError;
+    fn try_from(json_string: JsonString) -> Result<Self, Self::Error> {
+        default_try_from_json(json_string).map(ApiRequest)
+    }
+}
+
+impl<T: Serialize + Debug> From<ApiResponse<T>> for JsonString {
+    fn from(response: ApiResponse<T>) -> JsonString {
+        default_to_json(response.0)
+    }
+}
+
+impl<T: DeserializeOwned> TryFrom<JsonString> for ApiResponse<T> {
+    type Error = HolochainError;
+    fn try_from(json_string: JsonString) -> Result<Self, Self::Error> {
+        default_try_from_json(json_string).map(ApiResponse)
+    }
+}
+
+impl ApiResponse<JsonString> {
+    /// decodes the return code of a zome API call and reads its JSON from memory in one step
+    /// - Success is a null response
+    /// - Allocation is the JSON within the allocation of memory, @see read_json
+    /// - Failure is Err(HolochainError::Ribosome(..))
+    pub fn from_ribosome_return(
+        return_code: RibosomeEncodedValue,
+        memory: &[u8],
+    ) -> Result<ApiResponse<JsonString>, HolochainError> {
+        match return_code
+            .into_result()
+            .map_err(HolochainError::Ribosome)?
+        {
+            None => Ok(ApiResponse(JsonString::null())),
+            Some(allocation) => read_json::<JsonString>(memory, allocation).map(ApiResponse),
+        }
+    }
+
+    /// deserializes the untyped response into the type the call returns
+    pub fn into_typed<T: DeserializeOwned>(self) -> Result<ApiResponse<T>, HolochainError> {
+        ApiResponse::try_from(self.0)
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+
+    use super::*;
+    use holochain_core_types::error::RibosomeErrorCode;
+    use memory::allocation::{AllocationError, Length, Offset, WasmAllocation};
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Foo {
+        foo: String,
+    }
+
+    fn foo() -> Foo {
+        Foo {
+            foo: "bar".to_string(),
+        }
+    }
+
+    #[test]
+    fn api_request_round_trip_test() {
+        let json = JsonString::from(ApiRequest(foo()));
+        assert_eq!(JsonString::from("{\"foo\":\"bar\"}"), json);
+        assert_eq!(Ok(ApiRequest(foo())), ApiRequest::try_from(json));
+
+        let result: Result<ApiRequest<Foo>, HolochainError> =
+            ApiRequest::try_from(JsonString::from("{\"bar\":1}"));
+        assert!(result.unwrap_err().is_serialization_error());
+    }
+
+    #[test]
+    fn api_response_round_trip_test() {
+        let json = JsonString::from(ApiResponse(foo()));
+        assert_eq!(JsonString::from("{\"foo\":\"bar\"}"), json);
+        assert_eq!(Ok(ApiResponse(foo())), ApiResponse::try_from(json));
+    }
+
+    #[test]
+    fn from_ribosome_return_test() {
+        let memory = b"xx{\"foo\":\"bar\"}yy";
+        let allocation = WasmAllocation::new(Offset::from(2), Length::from(13)).unwrap();
+
+        let response =
+            ApiResponse::from_ribosome_return(RibosomeEncodedValue::from(allocation), memory);
+        assert_eq!(
+            Ok(ApiResponse(JsonString::from("{\"foo\":\"bar\"}"))),
+            response,
+        );
+        assert_eq!(Ok(ApiResponse(foo())), response.unwrap().into_typed());
+
+        assert_eq!(
+            Ok(ApiResponse(JsonString::null())),
+            ApiResponse::from_ribosome_return(RibosomeEncodedValue::Success, memory),
+        );
+
+        assert_eq!(
+            Err(HolochainError::Ribosome(RibosomeErrorCode::OutOfMemory)),
+            ApiResponse::from_ribosome_return(
+                RibosomeEncodedValue::Failure(RibosomeErrorCode::OutOfMemory),
+                memory,
+            ),
+        );
+
+        // the allocation must fit in memory
+        assert_eq!(
+            Err(HolochainError::from(AllocationError::OutOfBounds)),
+            ApiResponse::from_ribosome_return(
+                RibosomeEncodedValue::from(
+                    WasmAllocation::new(Offset::from(2), Length::from(100)).unwrap()
+                ),
+                memory,
+            ),
+        );
+    }
+
+}
@@ -1,3 +1,4 @@
+mod api_message;
 mod call;
 /// This module holds structs for all arguments and return types
 /// that get serialized and deserialized between core native and
@@ -18,4 +19,4 @@ mod update_entry;
 pub mod validation;
 mod zome_api_globals;
 
-pub use self::{call::*, query::*, update_entry::*, zome_api_globals::*};
+pub use self::{api_message::*, call::*, query::*, update_entry::*, zome_api_globals::*};
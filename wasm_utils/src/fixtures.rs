@@ -0,0 +1,113 @@
+//! Builders and fixtures for tests that need wasm memory layouts or ribosome values.
+//! Only available with the `test-utils` feature so production builds don't carry them.
+
+use holochain_core_types::error::{RibosomeEncodedValue, RibosomeErrorCode};
+use memory::{
+    allocation::{Length, Offset, WasmAllocation},
+    MemoryInt,
+};
+
+/// fluent construction of a WasmAllocation without magic numbers
+/// e.g. AllocationBuilder::new().at_offset(100).with_length(256).build()
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct AllocationBuilder {
+    offset: Offset,
+    length: Length,
+}
+
+impl Default for AllocationBuilder {
+    fn default() -> Self {
+        AllocationBuilder::new()
+    }
+}
+
+impl AllocationBuilder {
+    /// starts at offset 0 with length 1, the smallest valid allocation
+    pub fn new() -> AllocationBuilder {
+        AllocationBuilder {
+            offset: Offset::from(0),
+            length: Length::from(1),
+        }
+    }
+
+    pub fn at_offset(mut self, offset: MemoryInt) -> AllocationBuilder {
+        self.offset = Offset::from(offset);
+        self
+    }
+
+    pub fn with_length(mut self, length: MemoryInt) -> AllocationBuilder {
+        self.length = Length::from(length);
+        self
+    }
+
+    /// will panic if the built values are not a valid allocation
+    pub fn build(self) -> WasmAllocation {
+        WasmAllocation::new(self.offset, self.length).unwrap_or_else(|e| {
+            panic!(
+                "AllocationBuilder could not build a valid allocation from {:?}: {:?}",
+                self, e
+            )
+        })
+    }
+}
+
+pub struct ReturnCodeFixtures;
+
+impl ReturnCodeFixtures {
+    /// one RibosomeEncodedValue::Failure per RibosomeErrorCode variant
+    pub fn all_errors() -> Vec<RibosomeEncodedValue> {
+        RibosomeErrorCode::iter()
+            .map(RibosomeEncodedValue::Failure)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+
+    use fixtures::{AllocationBuilder, ReturnCodeFixtures};
+    use holochain_core_types::error::{
+        RibosomeEncodedValue, RibosomeEncodingBits, DOCUMENTED_CODES,
+    };
+    use memory::allocation::{Length, Offset, WasmAllocation};
+
+    #[test]
+    fn allocation_builder_test() {
+        assert_eq!(
+            WasmAllocation::new(Offset::from(100), Length::from(256)).unwrap(),
+            AllocationBuilder::new()
+                .at_offset(100)
+                .with_length(256)
+                .build(),
+        );
+
+        assert_eq!(
+            WasmAllocation::new(Offset::from(0), Length::from(1)).unwrap(),
+            AllocationBuilder::default().build(),
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn allocation_builder_zero_length_test() {
+        AllocationBuilder::new().with_length(0).build();
+    }
+
+    #[test]
+    fn all_errors_test() {
+        let errors = ReturnCodeFixtures::all_errors();
+        assert_eq!(12, errors.len());
+
+        for (error, (_, code, _)) in errors.into_iter().zip(DOCUMENTED_CODES) {
+            match error {
+                RibosomeEncodedValue::Failure(_) => (),
+                _ => panic!("all_errors should only contain failures"),
+            }
+            // discriminants are the documented codes shifted left 32 bits
+            assert_eq!(
+                (*code as RibosomeEncodingBits) << 32,
+                RibosomeEncodingBits::from(error),
+            );
+        }
+    }
+}
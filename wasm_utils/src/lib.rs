@@ -2,6 +2,8 @@
 //! or more generally for making rust code that the Ribosome can run.
 //! Must not have any dependency with any other Holochain crates.
 #![feature(try_from)]
+#[macro_use]
+extern crate lazy_static;
 extern crate serde;
 extern crate serde_json;
 #[macro_use]
@@ -14,9 +16,17 @@ pub extern crate holochain_core_types_derive;
 #[cfg_attr(tarpaulin, skip)]
 pub mod api_serialization;
 
+#[cfg(any(test, feature = "test-utils"))]
+pub mod fixtures;
 pub mod macros;
 pub mod memory;
 
+pub use memory::{
+    allocation::AllocationResultExt,
+    ribosome::{AllocationAsWasm, FromAllocationResult, IntoAllocationResult},
+    types::{MemoryBits, MemoryInt, MEMORY_INT_MAX},
+};
+
 pub fn wasm_target_dir(test_path: &str, wasm_path: &str) -> String {
     // this env var checker can't use holochain_common
     // crate because that uses `directories` crate which doesn't compile to WASM
@@ -1,9 +1,20 @@
-use holochain_core_types::{error::HolochainError, json::JsonString};
-use memory::{MemoryBits, MemoryInt, MEMORY_INT_MAX};
+use holochain_core_types::{
+    error::{HolochainError, RibosomeEncodedValue},
+    json::JsonString,
+};
+use memory::{ribosome::return_code_for_allocation_result, MemoryBits, MemoryInt, MEMORY_INT_MAX};
+use std::{
+    cmp,
+    convert::TryFrom,
+    error::Error,
+    fmt,
+    num::TryFromIntError,
+    sync::{PoisonError, RwLock},
+};
 
-#[derive(Copy, Clone, Debug, PartialEq)]
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Offset(MemoryInt);
-#[derive(Copy, Clone, Debug, PartialEq)]
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Length(MemoryInt);
 
 impl From<Offset> for MemoryInt {
@@ -12,6 +23,7 @@ impl From<Offset> for MemoryInt {
     }
 }
 
+/// MemoryBits is u64 so this also promotes offsets for hosts with 64 bit addressing
 impl From<Offset> for MemoryBits {
     fn from(offset: Offset) -> Self {
         offset.0 as MemoryBits
@@ -24,12 +36,35 @@ impl From<MemoryInt> for Offset {
     }
 }
 
+impl Offset {
+    /// the offset immediately after length bytes from this offset
+    /// OutOfBounds if that would be beyond the max memory
+    pub fn try_advance(self, length: Length) -> Result<Offset, AllocationError> {
+        let advanced = MemoryBits::from(self) + MemoryBits::from(length);
+        if advanced > MEMORY_INT_MAX {
+            Err(AllocationError::OutOfBounds)
+        } else {
+            Ok(Offset(advanced as MemoryInt))
+        }
+    }
+
+    /// the offset moved by a signed delta, e.g. when relocating allocations
+    /// OutOfBounds if that would be below zero or beyond the max memory
+    pub fn apply_delta(self, delta: i64) -> Result<Offset, AllocationError> {
+        let applied = i64::from(self.0)
+            .checked_add(delta)
+            .ok_or(AllocationError::OutOfBounds)?;
+        Ok(Offset(MemoryInt::try_from(applied)?))
+    }
+}
+
 impl From<Length> for MemoryInt {
     fn from(length: Length) -> Self {
         length.0
     }
 }
 
+/// MemoryBits is u64 so this also promotes lengths for hosts with 64 bit addressing
 impl From<Length> for MemoryBits {
     fn from(length: Length) -> Self {
         length.0 as MemoryBits
@@ -42,13 +77,60 @@ impl From<MemoryInt> for Length {
     }
 }
 
+impl Length {
+    /// the length of count records of stride bytes each
+    /// OutOfBounds if the product overflows MemoryInt, ZeroLength if the product is zero
+    pub fn from_count(count: MemoryInt, stride: MemoryInt) -> Result<Length, AllocationError> {
+        match count.checked_mul(stride) {
+            None => Err(AllocationError::OutOfBounds),
+            Some(0) => Err(AllocationError::ZeroLength),
+            Some(length) => Ok(Length(length)),
+        }
+    }
+
+    /// the length of len bytes, e.g. the len() of a buffer
+    /// OutOfBounds if len doesn't fit in MemoryInt, ZeroLength if len is zero
+    pub fn try_from_usize(len: usize) -> Result<Length, AllocationError> {
+        match MemoryInt::try_from(len)? {
+            0 => Err(AllocationError::ZeroLength),
+            length => Ok(Length(length)),
+        }
+    }
+}
+
 impl From<Length> for usize {
     fn from(length: Length) -> Self {
         length.0 as usize
     }
 }
 
+// hex formatting for debugging memory e.g. format!("{:#010x}", offset)
+impl fmt::LowerHex for Offset {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::LowerHex::fmt(&self.0, f)
+    }
+}
+
+impl fmt::UpperHex for Offset {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::UpperHex::fmt(&self.0, f)
+    }
+}
+
+impl fmt::LowerHex for Length {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::LowerHex::fmt(&self.0, f)
+    }
+}
+
+impl fmt::UpperHex for Length {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::UpperHex::fmt(&self.0, f)
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, DefaultJson, Clone, PartialEq)]
+#[must_use = "allocation errors must be handled or returned to the WASM guest"]
 pub enum AllocationError {
     /// (de)allocation is either too large or implies negative values
     OutOfBounds,
@@ -58,24 +140,146 @@ pub enum AllocationError {
     BadStackAlignment,
     /// writes can fail to serialize data before allocation occurs e.g. json
     Serialization,
+    /// allocation is valid but the wasm memory available to it is exhausted
+    /// requested is the end of the allocation, available is the memory limit
+    StackOverflow {
+        requested: MemoryBits,
+        available: MemoryBits,
+    },
+    /// allocation starts inside a region of memory reserved by the host e.g. for a header
+    ReservedRegion,
+    /// the host detected corrupt memory at runtime e.g. missing magic bytes at a known offset
+    Corrupt { reason: String },
+}
+
+impl AllocationError {
+    /// stable compact tag for each variant so that allocation errors can be carried in a few bits
+    /// 0 is never used as a tag
+    /// the fields of StackOverflow and Corrupt are not carried by the tag
+    pub fn as_code(&self) -> u8 {
+        match self {
+            AllocationError::OutOfBounds => 1,
+            AllocationError::ZeroLength => 2,
+            AllocationError::BadStackAlignment => 3,
+            AllocationError::Serialization => 4,
+            AllocationError::StackOverflow { .. } => 5,
+            AllocationError::ReservedRegion => 6,
+            AllocationError::Corrupt { .. } => 7,
+        }
+    }
+
+    /// inverse of as_code, None for unknown tags
+    /// StackOverflow decodes with zeroed fields and Corrupt with an empty reason as the tag does
+    /// not carry them
+    pub fn from_code(code: u8) -> Option<AllocationError> {
+        match code {
+            1 => Some(AllocationError::OutOfBounds),
+            2 => Some(AllocationError::ZeroLength),
+            3 => Some(AllocationError::BadStackAlignment),
+            4 => Some(AllocationError::Serialization),
+            5 => Some(AllocationError::StackOverflow {
+                requested: 0,
+                available: 0,
+            }),
+            6 => Some(AllocationError::ReservedRegion),
+            7 => Some(AllocationError::Corrupt {
+                reason: String::new(),
+            }),
+            _ => None,
+        }
+    }
+
+    /// explicit form of From<AllocationError> for HolochainError
+    /// every allocation error becomes HolochainError::ErrorGeneric with the String of the error
+    /// as its message, e.g. "Allocation out of bounds", which includes the reason of Corrupt
+    /// for the ribosome encoding of an allocation error use RibosomeErrorCode::from instead
+    pub fn into_holochain_error(self) -> HolochainError {
+        HolochainError::ErrorGeneric(String::from(self))
+    }
 }
 
+impl fmt::Display for AllocationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AllocationError::OutOfBounds => write!(f, "Allocation out of bounds"),
+            AllocationError::ZeroLength => write!(f, "Allocation is zero length"),
+            AllocationError::BadStackAlignment => write!(f, "Allocation not aligned with stack"),
+            AllocationError::Serialization => write!(f, "Allocation could not serialize data"),
+            AllocationError::StackOverflow {
+                requested,
+                available,
+            } => write!(
+                f,
+                "Allocation overflows available memory: requested {} of {}",
+                requested, available
+            ),
+            AllocationError::ReservedRegion => write!(f, "Allocation intrudes on reserved memory"),
+            AllocationError::Corrupt { reason } => {
+                write!(f, "Memory corruption detected: {}", reason)
+            }
+        }
+    }
+}
+
+impl Error for AllocationError {}
+
 impl From<AllocationError> for String {
     fn from(allocation_error: AllocationError) -> Self {
-        match allocation_error {
-            AllocationError::OutOfBounds => "Allocation out of bounds",
-            AllocationError::ZeroLength => "Allocation is zero length",
-            AllocationError::BadStackAlignment => "Allocation not aligned with stack",
-            AllocationError::Serialization => "Allocation could not serialize data",
-        }
-        .into()
+        allocation_error.to_string()
     }
 }
 
 impl From<AllocationError> for HolochainError {
     fn from(allocation_error: AllocationError) -> Self {
-        HolochainError::ErrorGeneric(String::from(allocation_error))
+        allocation_error.into_holochain_error()
+    }
+}
+
+/// the serde error is dropped as Serialization does not carry a message
+/// e.g. serde_json::to_string(&data)? before writing the json to memory
+impl From<serde_json::Error> for AllocationError {
+    fn from(_: serde_json::Error) -> Self {
+        AllocationError::Serialization
+    }
+}
+
+/// integer downcasts that don't fit are out of bounds
+impl From<TryFromIntError> for AllocationError {
+    fn from(_: TryFromIntError) -> Self {
+        AllocationError::OutOfBounds
+    }
+}
+
+lazy_static! {
+    /// None when no hook is set, only locked on the failure path
+    static ref ALLOCATION_ERROR_HOOK: RwLock<Option<fn(&AllocationError)>> = RwLock::new(None);
+}
+
+/// registers a hook called whenever constructing a WasmAllocation fails
+/// e.g. to wire allocation failures into a metrics system
+/// replaces any previously set hook
+pub fn set_allocation_error_hook(hook: fn(&AllocationError)) {
+    *ALLOCATION_ERROR_HOOK
+        .write()
+        .unwrap_or_else(PoisonError::into_inner) = Some(hook);
+}
+
+pub fn clear_allocation_error_hook() {
+    *ALLOCATION_ERROR_HOOK
+        .write()
+        .unwrap_or_else(PoisonError::into_inner) = None;
+}
+
+/// passes the error through the hook, if any
+/// only ever called on failure so there is no cost to successful allocations
+fn report_allocation_error(allocation_error: AllocationError) -> AllocationError {
+    let hook = *ALLOCATION_ERROR_HOOK
+        .read()
+        .unwrap_or_else(PoisonError::into_inner);
+    if let Some(hook) = hook {
+        hook(&allocation_error);
     }
+    allocation_error
 }
 
 #[derive(Copy, Clone, Debug, PartialEq)]
@@ -87,20 +291,192 @@ pub struct WasmAllocation {
 
 impl WasmAllocation {
     // represent the max as MemoryBits type to allow gt comparisons
-    pub fn max() -> MemoryBits {
+    // const so it can be used in const contexts e.g. array sizes
+    pub const fn max() -> MemoryBits {
         MEMORY_INT_MAX
     }
 
     pub fn new(offset: Offset, length: Length) -> AllocationResult {
         if (MemoryBits::from(offset) + MemoryBits::from(length)) > WasmAllocation::max() {
-            Err(AllocationError::OutOfBounds)
+            Err(report_allocation_error(AllocationError::OutOfBounds))
         } else if MemoryInt::from(length) == 0 {
-            Err(AllocationError::ZeroLength)
+            Err(report_allocation_error(AllocationError::ZeroLength))
         } else {
             Ok(WasmAllocation { offset, length })
         }
     }
 
+    /// always a valid allocation, never an error
+    /// length is clamped to at least 1 and at most the space remaining after offset
+    /// offset is clamped so that at least 1 byte remains
+    /// only for internal use where the inputs are known to be bounded e.g. telemetry counters
+    /// as clamping silently changes the allocation, use new() for anything else
+    pub fn new_saturating(offset: Offset, length: Length) -> WasmAllocation {
+        let max = WasmAllocation::max() as MemoryInt;
+        let offset = MemoryInt::from(offset).min(max - 1);
+        let length = MemoryInt::from(length).min(max - offset).max(1);
+        WasmAllocation {
+            offset: Offset(offset),
+            length: Length(length),
+        }
+    }
+
+    /// same as new() but a zero length is a legitimately empty payload rather than an error
+    /// Ok(None) is empty, Ok(Some(..)) is an allocation and Err(..) is any other failure
+    pub fn new_or_empty(
+        offset: Offset,
+        length: Length,
+    ) -> Result<Option<WasmAllocation>, AllocationError> {
+        if MemoryInt::from(length) == 0 {
+            Ok(None)
+        } else {
+            WasmAllocation::new(offset, length).map(Some)
+        }
+    }
+
+    /// same as new() but also fails with StackOverflow if the allocation would end beyond
+    /// memory_limit, e.g. the number of bytes in the currently available wasm pages
+    pub fn new_with_limit(
+        offset: Offset,
+        length: Length,
+        memory_limit: MemoryBits,
+    ) -> AllocationResult {
+        let allocation = WasmAllocation::new(offset, length)?;
+        let requested = MemoryBits::from(offset) + MemoryBits::from(length);
+        if requested > memory_limit {
+            Err(report_allocation_error(AllocationError::StackOverflow {
+                requested,
+                available: memory_limit,
+            }))
+        } else {
+            Ok(allocation)
+        }
+    }
+
+    /// same as new() but also fails with OutOfBounds if the allocation would end beyond
+    /// committed_len, the number of bytes actually committed to the wasm memory right now
+    /// memory that could grow but hasn't yet is not addressable so MEMORY_INT_MAX isn't enough
+    pub fn new_within(
+        offset: Offset,
+        length: Length,
+        committed_len: MemoryInt,
+    ) -> AllocationResult {
+        let allocation = WasmAllocation::new(offset, length)?;
+        if MemoryBits::from(offset) + MemoryBits::from(length) > MemoryBits::from(committed_len) {
+            Err(report_allocation_error(AllocationError::OutOfBounds))
+        } else {
+            Ok(allocation)
+        }
+    }
+
+    /// same as new() but also fails with ReservedRegion if the allocation starts below
+    /// reserved_end, e.g. so allocations never clobber a header at the start of wasm memory
+    pub fn new_above(offset: Offset, length: Length, reserved_end: Offset) -> AllocationResult {
+        let allocation = WasmAllocation::new(offset, length)?;
+        if MemoryInt::from(offset) < MemoryInt::from(reserved_end) {
+            Err(report_allocation_error(AllocationError::ReservedRegion))
+        } else {
+            Ok(allocation)
+        }
+    }
+
+    /// same as new() but also fails with OutOfBounds if length exceeds max_length
+    /// e.g. so hosts can enforce a maximum JSON response size as part of the allocation
+    pub fn with_length_checked(
+        offset: Offset,
+        length: Length,
+        max_length: Length,
+    ) -> AllocationResult {
+        let allocation = WasmAllocation::new(offset, length)?;
+        if MemoryInt::from(length) > MemoryInt::from(max_length) {
+            Err(report_allocation_error(AllocationError::OutOfBounds))
+        } else {
+            Ok(allocation)
+        }
+    }
+
+    /// the allocation from start up to but excluding end
+    /// OutOfBounds if end is before start, ZeroLength if they are equal
+    pub fn from_range(start: MemoryInt, end: MemoryInt) -> AllocationResult {
+        match end.checked_sub(start) {
+            Some(length) => WasmAllocation::new(Offset::from(start), Length::from(length)),
+            None => Err(report_allocation_error(AllocationError::OutOfBounds)),
+        }
+    }
+
+    /// the region covered by both allocations, None if they don't overlap
+    /// adjacent allocations don't overlap
+    pub fn overlapping_region(self, other: WasmAllocation) -> Option<WasmAllocation> {
+        let start = std::cmp::max(
+            MemoryBits::from(self.offset),
+            MemoryBits::from(other.offset),
+        );
+        let end = std::cmp::min(
+            MemoryBits::from(self.offset) + MemoryBits::from(self.length),
+            MemoryBits::from(other.offset) + MemoryBits::from(other.length),
+        );
+        if start < end {
+            // a region inside a valid allocation is always valid
+            Some(WasmAllocation {
+                offset: Offset(start as MemoryInt),
+                length: Length((end - start) as MemoryInt),
+            })
+        } else {
+            None
+        }
+    }
+
+    /// the smallest allocation covering both allocations, including any gap between them
+    pub fn union(self, other: WasmAllocation) -> WasmAllocation {
+        let start = std::cmp::min(
+            MemoryBits::from(self.offset),
+            MemoryBits::from(other.offset),
+        );
+        let end = std::cmp::max(
+            MemoryBits::from(self.offset) + MemoryBits::from(self.length),
+            MemoryBits::from(other.offset) + MemoryBits::from(other.length),
+        );
+        // both allocations are valid so the covering region is within bounds and not empty
+        WasmAllocation {
+            offset: Offset(start as MemoryInt),
+            length: Length((end - start) as MemoryInt),
+        }
+    }
+
+    /// offset then length, each as little endian MemoryInt bytes
+    pub fn to_le_bytes(self) -> [u8; 8] {
+        let mut bytes = [0; 8];
+        bytes[..4].copy_from_slice(&self.offset.0.to_le_bytes());
+        bytes[4..].copy_from_slice(&self.length.0.to_le_bytes());
+        bytes
+    }
+
+    /// inverse of to_le_bytes, validated as per new()
+    pub fn from_le_bytes(bytes: [u8; 8]) -> AllocationResult {
+        let (offset_bytes, length_bytes) = split_allocation_bytes(bytes);
+        WasmAllocation::new(
+            Offset(MemoryInt::from_le_bytes(offset_bytes)),
+            Length(MemoryInt::from_le_bytes(length_bytes)),
+        )
+    }
+
+    /// offset then length, each as big endian MemoryInt bytes
+    pub fn to_be_bytes(self) -> [u8; 8] {
+        let mut bytes = [0; 8];
+        bytes[..4].copy_from_slice(&self.offset.0.to_be_bytes());
+        bytes[4..].copy_from_slice(&self.length.0.to_be_bytes());
+        bytes
+    }
+
+    /// inverse of to_be_bytes, validated as per new()
+    pub fn from_be_bytes(bytes: [u8; 8]) -> AllocationResult {
+        let (offset_bytes, length_bytes) = split_allocation_bytes(bytes);
+        WasmAllocation::new(
+            Offset(MemoryInt::from_be_bytes(offset_bytes)),
+            Length(MemoryInt::from_be_bytes(length_bytes)),
+        )
+    }
+
     pub fn offset(self) -> Offset {
         self.offset
     }
@@ -108,18 +484,133 @@ impl WasmAllocation {
     pub fn length(self) -> Length {
         self.length
     }
+
+    /// the offset immediately after the allocation, i.e. offset + length
+    /// can't overflow as new() guarantees the allocation ends at or before MEMORY_INT_MAX
+    pub fn end_offset(self) -> Offset {
+        Offset(self.offset.0 + self.length.0)
+    }
+
+    /// true if the offset is a multiple of align, e.g. before reading a #[repr(C)] struct
+    /// @see align_up_offset to round an offset up to an alignment
+    /// nothing is aligned to zero
+    pub fn is_aligned_to(self, align: MemoryInt) -> bool {
+        align != 0 && self.offset.0 % align == 0
+    }
+
+    /// true if the allocation doesn't straddle a boundary between pages of page_size bytes
+    /// e.g. allocation.is_single_page(Length::from(U16_MAX + 1)) for a 64KiB wasm page
+    /// a zero page_size has no pages so is always false
+    pub fn is_single_page(self, page_size: Length) -> bool {
+        self.page_index(page_size).is_some()
+    }
+
+    /// the index of the page of page_size bytes that the allocation is within
+    /// None if the allocation straddles a page boundary or page_size is zero
+    pub fn page_index(self, page_size: Length) -> Option<MemoryInt> {
+        if page_size.0 == 0 {
+            return None;
+        }
+        // length is never zero so the last byte is always within the allocation
+        let first_page = self.offset.0 / page_size.0;
+        let last_page = (self.offset.0 + (self.length.0 - 1)) / page_size.0;
+        if first_page == last_page {
+            Some(first_page)
+        } else {
+            None
+        }
+    }
+
+    /// orders allocations by length, then by offset for allocations of the same length
+    /// e.g. allocations.sort_by(WasmAllocation::cmp_by_length) for best fit free lists
+    /// not an Ord impl because there is no single natural order for regions of memory
+    pub fn cmp_by_length(&self, other: &WasmAllocation) -> cmp::Ordering {
+        (self.length.0, self.offset.0).cmp(&(other.length.0, other.offset.0))
+    }
 }
 
+/// iterates over the address of every byte in the allocation
+impl IntoIterator for WasmAllocation {
+    type Item = usize;
+    type IntoIter = std::ops::Range<usize>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        let start = MemoryInt::from(self.offset) as usize;
+        start..start + usize::from(self.length)
+    }
+}
+
+/// Result is already #[must_use] so AllocationResult needs no attribute of its own
 pub type AllocationResult = Result<WasmAllocation, AllocationError>;
 
+/// method syntax for return_code_for_allocation_result
+/// e.g. stack.next_allocation(length).into_ribosome_return()
+pub trait AllocationResultExt {
+    fn into_ribosome_return(self) -> RibosomeEncodedValue;
+}
+
+impl AllocationResultExt for AllocationResult {
+    fn into_ribosome_return(self) -> RibosomeEncodedValue {
+        return_code_for_allocation_result(self)
+    }
+}
+
+/// offset and length halves of WasmAllocation byte serializations
+fn split_allocation_bytes(bytes: [u8; 8]) -> ([u8; 4], [u8; 4]) {
+    let mut offset_bytes = [0; 4];
+    let mut length_bytes = [0; 4];
+    offset_bytes.copy_from_slice(&bytes[..4]);
+    length_bytes.copy_from_slice(&bytes[4..]);
+    (offset_bytes, length_bytes)
+}
+
+/// the number of bytes needed to allocate the serialized json
+/// ZeroLength for empty json as it can't be allocated
+pub fn json_allocation_length(json: &JsonString) -> Result<Length, AllocationError> {
+    let json_str: &str = json.into();
+    let length = json_str.len() as MemoryBits;
+    if length > WasmAllocation::max() {
+        Err(AllocationError::OutOfBounds)
+    } else if length == 0 {
+        Err(AllocationError::ZeroLength)
+    } else {
+        Ok(Length(length as MemoryInt))
+    }
+}
+
+/// rounds offset up to the nearest multiple of align, e.g. 8 for i64 values
+/// align must be a power of two, otherwise BadStackAlignment
+/// OutOfBounds if the rounded offset does not fit in memory
+pub fn align_up_offset(offset: Offset, align: MemoryInt) -> Result<Offset, AllocationError> {
+    if !align.is_power_of_two() {
+        return Err(AllocationError::BadStackAlignment);
+    }
+    let align = MemoryBits::from(align);
+    let aligned = (MemoryBits::from(offset) + align - 1) & !(align - 1);
+    if aligned > MEMORY_INT_MAX {
+        Err(AllocationError::OutOfBounds)
+    } else {
+        Ok(Offset(aligned as MemoryInt))
+    }
+}
+
 #[cfg(test)]
 pub mod tests {
 
-    use holochain_core_types::{bits_n_pieces::U16_MAX, error::HolochainError};
+    use holochain_core_types::{
+        bits_n_pieces::U16_MAX,
+        error::{HolochainError, RibosomeEncodedValue, RibosomeErrorCode},
+        json::JsonString,
+    };
     use memory::{
-        allocation::{AllocationError, Length, Offset, WasmAllocation},
+        allocation::{
+            align_up_offset, clear_allocation_error_hook, json_allocation_length,
+            set_allocation_error_hook, AllocationError, AllocationResult, AllocationResultExt,
+            Length, Offset, WasmAllocation,
+        },
         MemoryBits, MemoryInt, MEMORY_INT_MAX,
     };
+    use std::{cell::RefCell, convert::TryFrom};
 
     pub fn fake_offset() -> Offset {
         Offset(12345)
@@ -144,6 +635,63 @@ pub mod tests {
         assert_eq!(fake_offset(), Offset::from(12345 as MemoryInt),);
     }
 
+    #[test]
+    pub fn offset_try_advance_test() {
+        assert_eq!(
+            Ok(Offset::from(12345 * 2)),
+            fake_offset().try_advance(fake_length()),
+        );
+
+        assert_eq!(
+            Ok(fake_offset()),
+            fake_offset().try_advance(Length::from(0)),
+        );
+
+        // advance until the top of memory
+        let half = Length::from(std::u32::MAX / 2);
+        let mut offset = Offset::from(0);
+        for _ in 0..2 {
+            offset = offset.try_advance(half).unwrap();
+        }
+        offset = offset.try_advance(Length::from(1)).unwrap();
+        assert_eq!(Offset::from(std::u32::MAX), offset);
+
+        assert_eq!(
+            Err(AllocationError::OutOfBounds),
+            offset.try_advance(Length::from(1)),
+        );
+    }
+
+    #[test]
+    pub fn offset_apply_delta_test() {
+        assert_eq!(Ok(Offset::from(12350)), fake_offset().apply_delta(5));
+        assert_eq!(Ok(Offset::from(12340)), fake_offset().apply_delta(-5));
+        assert_eq!(Ok(Offset::from(0)), fake_offset().apply_delta(-12345));
+        assert_eq!(Ok(fake_offset()), fake_offset().apply_delta(0));
+
+        assert_eq!(
+            Err(AllocationError::OutOfBounds),
+            fake_offset().apply_delta(-12346),
+        );
+        assert_eq!(
+            Err(AllocationError::OutOfBounds),
+            fake_offset().apply_delta(std::i64::MIN),
+        );
+
+        assert_eq!(
+            Ok(Offset::from(std::u32::MAX)),
+            Offset::from(0).apply_delta(i64::from(std::u32::MAX)),
+        );
+        assert_eq!(
+            Err(AllocationError::OutOfBounds),
+            Offset::from(1).apply_delta(i64::from(std::u32::MAX)),
+        );
+        assert_eq!(
+            Err(AllocationError::OutOfBounds),
+            fake_offset().apply_delta(std::i64::MAX),
+        );
+    }
+
     #[test]
     pub fn memory_int_from_length_test() {
         assert_eq!(12345 as MemoryInt, MemoryInt::from(fake_length()),);
@@ -159,11 +707,81 @@ pub mod tests {
         assert_eq!(fake_length(), Length::from(12345 as MemoryInt),);
     }
 
+    #[test]
+    pub fn length_from_count_test() {
+        assert_eq!(Ok(Length::from(24)), Length::from_count(3, 8));
+        assert_eq!(
+            Ok(Length::from(std::u32::MAX)),
+            Length::from_count(1, std::u32::MAX)
+        );
+
+        assert_eq!(Err(AllocationError::ZeroLength), Length::from_count(0, 8));
+        assert_eq!(Err(AllocationError::ZeroLength), Length::from_count(3, 0));
+
+        assert_eq!(
+            Err(AllocationError::OutOfBounds),
+            Length::from_count(std::u32::MAX, 2),
+        );
+        assert_eq!(
+            Err(AllocationError::OutOfBounds),
+            Length::from_count(U16_MAX * 2, U16_MAX * 2),
+        );
+    }
+
+    #[test]
+    pub fn length_try_from_usize_test() {
+        assert_eq!(Ok(Length::from(24)), Length::try_from_usize(24));
+        assert_eq!(
+            Ok(Length::from(std::u32::MAX)),
+            Length::try_from_usize(std::u32::MAX as usize),
+        );
+
+        assert_eq!(Err(AllocationError::ZeroLength), Length::try_from_usize(0));
+
+        assert_eq!(
+            Err(AllocationError::OutOfBounds),
+            Length::try_from_usize(std::u32::MAX as usize + 1),
+        );
+    }
+
+    #[test]
+    pub fn allocation_error_from_try_from_int_error_test() {
+        assert_eq!(
+            AllocationError::OutOfBounds,
+            AllocationError::from(u8::try_from(256_u32).unwrap_err()),
+        );
+    }
+
+    #[test]
+    /// MemoryBits is u64 so From<Offset> and From<Length> for u64 come for free
+    pub fn u64_from_offset_and_length_test() {
+        assert_eq!(12345_u64, u64::from(fake_offset()));
+        assert_eq!(12345_u64, u64::from(fake_length()));
+
+        // promotion is lossless at the top of the 32 bit range
+        assert_eq!(std::u32::MAX as u64, u64::from(Offset::from(std::u32::MAX)));
+        assert_eq!(std::u32::MAX as u64, u64::from(Length::from(std::u32::MAX)));
+    }
+
     #[test]
     pub fn usize_from_length_test() {
         assert_eq!(usize::from(fake_length()), 12345 as usize,);
     }
 
+    #[test]
+    pub fn hex_offset_test() {
+        assert_eq!("3039", format!("{:x}", fake_offset()));
+        assert_eq!("0x00003039", format!("{:#010x}", fake_offset()));
+        assert_eq!("ABCDEF", format!("{:X}", Offset::from(0xabcdef)));
+    }
+
+    #[test]
+    pub fn hex_length_test() {
+        assert_eq!("3039", format!("{:x}", fake_length()));
+        assert_eq!("0x00003039", format!("{:#010x}", fake_length()));
+        assert_eq!("ABCDEF", format!("{:X}", Length::from(0xabcdef)));
+    }
+
     #[test]
     pub fn string_from_allocation_test() {
         assert_eq!(
@@ -182,6 +800,115 @@ pub mod tests {
             String::from("Allocation could not serialize data"),
             String::from(AllocationError::Serialization),
         );
+        assert_eq!(
+            String::from("Allocation overflows available memory: requested 10 of 5"),
+            String::from(AllocationError::StackOverflow {
+                requested: 10,
+                available: 5,
+            }),
+        );
+        assert_eq!(
+            String::from("Allocation intrudes on reserved memory"),
+            String::from(AllocationError::ReservedRegion),
+        );
+        assert_eq!(
+            String::from("Memory corruption detected: missing magic bytes"),
+            String::from(AllocationError::Corrupt {
+                reason: "missing magic bytes".into(),
+            }),
+        );
+    }
+
+    #[test]
+    pub fn allocation_error_display_test() {
+        for allocation_error in vec![
+            AllocationError::OutOfBounds,
+            AllocationError::ZeroLength,
+            AllocationError::BadStackAlignment,
+            AllocationError::Serialization,
+            AllocationError::StackOverflow {
+                requested: 10,
+                available: 5,
+            },
+            AllocationError::ReservedRegion,
+            AllocationError::Corrupt {
+                reason: "missing magic bytes".into(),
+            },
+        ] {
+            assert_eq!(
+                String::from(allocation_error.clone()),
+                format!("{}", allocation_error),
+            );
+        }
+
+        assert_eq!(
+            "Allocation out of bounds",
+            AllocationError::OutOfBounds.to_string(),
+        );
+        assert_eq!(
+            "Allocation overflows available memory: requested 10 of 5",
+            AllocationError::StackOverflow {
+                requested: 10,
+                available: 5,
+            }
+            .to_string(),
+        );
+    }
+
+    #[test]
+    pub fn allocation_error_code_round_trip_test() {
+        for allocation_error in vec![
+            AllocationError::OutOfBounds,
+            AllocationError::ZeroLength,
+            AllocationError::BadStackAlignment,
+            AllocationError::Serialization,
+            AllocationError::StackOverflow {
+                requested: 0,
+                available: 0,
+            },
+            AllocationError::ReservedRegion,
+            AllocationError::Corrupt {
+                reason: String::new(),
+            },
+        ] {
+            assert_eq!(
+                Some(allocation_error.clone()),
+                AllocationError::from_code(allocation_error.as_code()),
+            );
+        }
+
+        // fields are not carried by the code
+        assert_eq!(
+            Some(AllocationError::StackOverflow {
+                requested: 0,
+                available: 0,
+            }),
+            AllocationError::from_code(
+                AllocationError::StackOverflow {
+                    requested: 10,
+                    available: 5,
+                }
+                .as_code()
+            ),
+        );
+        assert_eq!(
+            Some(AllocationError::Corrupt {
+                reason: String::new(),
+            }),
+            AllocationError::from_code(
+                AllocationError::Corrupt {
+                    reason: "missing magic bytes".into(),
+                }
+                .as_code()
+            ),
+        );
+    }
+
+    #[test]
+    pub fn allocation_error_from_unknown_code_test() {
+        assert_eq!(None, AllocationError::from_code(0));
+        assert_eq!(None, AllocationError::from_code(8));
+        assert_eq!(None, AllocationError::from_code(std::u8::MAX));
     }
 
     #[test]
@@ -205,15 +932,101 @@ pub mod tests {
     }
 
     #[test]
-    pub fn allocation_max_test() {
-        assert_eq!(MEMORY_INT_MAX, WasmAllocation::max(),);
+    pub fn allocation_error_from_serde_json_error_test() {
+        fn to_json<T: serde::Serialize>(data: &T) -> Result<String, AllocationError> {
+            Ok(serde_json::to_string(data)?)
+        }
+
+        // json object keys must be strings
+        let mut map = std::collections::BTreeMap::new();
+        map.insert(vec![1_u8], 1_u8);
+        assert_eq!(Err(AllocationError::Serialization), to_json(&map));
+
+        assert_eq!(Ok("[1,2]".to_string()), to_json(&vec![1, 2]));
     }
 
     #[test]
-    pub fn allocation_new_test() {
+    pub fn into_holochain_error_test() {
+        for allocation_error in vec![
+            AllocationError::OutOfBounds,
+            AllocationError::ZeroLength,
+            AllocationError::BadStackAlignment,
+            AllocationError::Serialization,
+            AllocationError::StackOverflow {
+                requested: 2,
+                available: 1,
+            },
+            AllocationError::ReservedRegion,
+            AllocationError::Corrupt {
+                reason: "wrong entry type tag".into(),
+            },
+        ] {
+            assert_eq!(
+                HolochainError::ErrorGeneric(String::from(allocation_error.clone())),
+                allocation_error.clone().into_holochain_error(),
+            );
+            assert_eq!(
+                HolochainError::from(allocation_error.clone()),
+                allocation_error.into_holochain_error(),
+            );
+        }
+    }
+
+    #[test]
+    pub fn corrupt_propagation_test() {
+        fn check_magic(memory: &[u8]) -> Result<(), AllocationError> {
+            if memory.starts_with(b"HC") {
+                Ok(())
+            } else {
+                Err(AllocationError::Corrupt {
+                    reason: "missing magic bytes".into(),
+                })
+            }
+        }
+        fn host_fn(memory: &[u8]) -> Result<(), HolochainError> {
+            check_magic(memory)?;
+            Ok(())
+        }
+
+        assert_eq!(Ok(()), host_fn(b"HC.."));
         assert_eq!(
-            Err(AllocationError::OutOfBounds),
-            WasmAllocation::new(Offset::from(std::u32::MAX), Length::from(1)),
+            Err(HolochainError::ErrorGeneric(
+                "Memory corruption detected: missing magic bytes".into()
+            )),
+            host_fn(b"...."),
+        );
+
+        let allocation_error = check_magic(b"....").unwrap_err();
+        assert_eq!(
+            RibosomeEncodedValue::Failure(RibosomeErrorCode::Unspecified),
+            RibosomeEncodedValue::from(allocation_error.clone()),
+        );
+        assert_eq!(
+            Err(HolochainError::Ribosome(RibosomeErrorCode::Unspecified)),
+            RibosomeEncodedValue::from(allocation_error).propagate_err(),
+        );
+    }
+
+    #[test]
+    pub fn allocation_max_test() {
+        assert_eq!(MEMORY_INT_MAX, WasmAllocation::max(),);
+    }
+
+    #[test]
+    pub fn allocation_max_const_test() {
+        const MAX: MemoryBits = WasmAllocation::max();
+        assert_eq!(MEMORY_INT_MAX, MAX);
+
+        // usable for array sizes
+        let bytes = [0_u8; (WasmAllocation::max() >> 29) as usize];
+        assert_eq!(7, bytes.len());
+    }
+
+    #[test]
+    pub fn allocation_new_test() {
+        assert_eq!(
+            Err(AllocationError::OutOfBounds),
+            WasmAllocation::new(Offset::from(std::u32::MAX), Length::from(1)),
         );
 
         assert_eq!(
@@ -240,6 +1053,277 @@ pub mod tests {
         );
     }
 
+    thread_local! {
+        // hooks are global but tests run in parallel threads
+        static HOOKED_ERRORS: RefCell<Vec<AllocationError>> = RefCell::new(Vec::new());
+    }
+
+    fn record_allocation_error(allocation_error: &AllocationError) {
+        HOOKED_ERRORS.with(|errors| errors.borrow_mut().push(allocation_error.clone()));
+    }
+
+    #[test]
+    pub fn allocation_error_hook_test() {
+        set_allocation_error_hook(record_allocation_error);
+
+        assert!(WasmAllocation::new(Offset::from(1), Length::from(1)).is_ok());
+        HOOKED_ERRORS.with(|errors| assert!(errors.borrow().is_empty()));
+
+        assert_eq!(
+            Err(AllocationError::ZeroLength),
+            WasmAllocation::new(Offset::from(1), Length::from(0)),
+        );
+        HOOKED_ERRORS.with(|errors| {
+            assert_eq!(vec![AllocationError::ZeroLength], *errors.borrow());
+        });
+
+        clear_allocation_error_hook();
+    }
+
+    #[test]
+    pub fn allocation_new_with_limit_test() {
+        assert_eq!(
+            Err(AllocationError::OutOfBounds),
+            WasmAllocation::new_with_limit(Offset::from(std::u32::MAX), Length::from(1), 10),
+        );
+
+        assert_eq!(
+            Err(AllocationError::ZeroLength),
+            WasmAllocation::new_with_limit(Offset::from(1), Length::from(0), 10),
+        );
+
+        assert_eq!(
+            Err(AllocationError::StackOverflow {
+                requested: 11,
+                available: 10,
+            }),
+            WasmAllocation::new_with_limit(Offset::from(5), Length::from(6), 10),
+        );
+
+        // an allocation ending exactly at the limit fits
+        assert_eq!(
+            WasmAllocation::new(Offset::from(5), Length::from(5)),
+            WasmAllocation::new_with_limit(Offset::from(5), Length::from(5), 10),
+        );
+
+        // one wasm page
+        let page = U16_MAX as MemoryBits + 1;
+        assert_eq!(
+            Err(AllocationError::StackOverflow {
+                requested: page + 1,
+                available: page,
+            }),
+            WasmAllocation::new_with_limit(Offset::from(U16_MAX), Length::from(2), page),
+        );
+    }
+
+    #[test]
+    pub fn offset_length_serde_test() {
+        assert_eq!("5", serde_json::to_string(&Offset::from(5)).unwrap());
+        assert_eq!("7", serde_json::to_string(&Length::from(7)).unwrap());
+        assert_eq!(
+            Offset::from(5),
+            serde_json::from_str::<Offset>("5").unwrap()
+        );
+        assert_eq!(
+            Length::from(7),
+            serde_json::from_str::<Length>("7").unwrap()
+        );
+    }
+
+    #[test]
+    pub fn allocation_new_within_test() {
+        // one wasm page committed
+        let committed = U16_MAX + 1;
+
+        // within committed memory
+        assert_eq!(
+            WasmAllocation::new(Offset::from(5), Length::from(5)),
+            WasmAllocation::new_within(Offset::from(5), Length::from(5), committed),
+        );
+        // ending exactly at the committed length fits
+        assert_eq!(
+            WasmAllocation::new(Offset::from(U16_MAX), Length::from(1)),
+            WasmAllocation::new_within(Offset::from(U16_MAX), Length::from(1), committed),
+        );
+
+        // beyond committed memory but under the absolute max
+        assert_eq!(
+            Err(AllocationError::OutOfBounds),
+            WasmAllocation::new_within(Offset::from(U16_MAX), Length::from(2), committed),
+        );
+
+        // over the absolute max regardless of committed memory
+        assert_eq!(
+            Err(AllocationError::OutOfBounds),
+            WasmAllocation::new_within(Offset::from(std::u32::MAX), Length::from(1), std::u32::MAX),
+        );
+
+        assert_eq!(
+            Err(AllocationError::ZeroLength),
+            WasmAllocation::new_within(Offset::from(1), Length::from(0), committed),
+        );
+    }
+
+    #[test]
+    pub fn allocation_new_above_test() {
+        let reserved_end = Offset::from(16);
+
+        // starting exactly at the reserved end is allowed
+        assert_eq!(
+            WasmAllocation::new(Offset::from(16), Length::from(4)),
+            WasmAllocation::new_above(Offset::from(16), Length::from(4), reserved_end),
+        );
+
+        // starting inside the reserved region is rejected
+        assert_eq!(
+            Err(AllocationError::ReservedRegion),
+            WasmAllocation::new_above(Offset::from(15), Length::from(4), reserved_end),
+        );
+        assert_eq!(
+            Err(AllocationError::ReservedRegion),
+            WasmAllocation::new_above(Offset::from(0), Length::from(1), reserved_end),
+        );
+
+        // without a reserved region it is the same as new()
+        assert_eq!(
+            WasmAllocation::new(Offset::from(0), Length::from(1)),
+            WasmAllocation::new_above(Offset::from(0), Length::from(1), Offset::from(0)),
+        );
+
+        // regular errors take priority
+        assert_eq!(
+            Err(AllocationError::ZeroLength),
+            WasmAllocation::new_above(Offset::from(0), Length::from(0), reserved_end),
+        );
+    }
+
+    #[test]
+    pub fn allocation_with_length_checked_test() {
+        let max_length = Length::from(1024);
+
+        // up to and including the max length is allowed
+        assert_eq!(
+            WasmAllocation::new(Offset::from(8), Length::from(1024)),
+            WasmAllocation::with_length_checked(Offset::from(8), Length::from(1024), max_length),
+        );
+        assert_eq!(
+            WasmAllocation::new(Offset::from(8), Length::from(1)),
+            WasmAllocation::with_length_checked(Offset::from(8), Length::from(1), max_length),
+        );
+
+        // over budget is rejected
+        assert_eq!(
+            Err(AllocationError::OutOfBounds),
+            WasmAllocation::with_length_checked(Offset::from(8), Length::from(1025), max_length),
+        );
+
+        // regular errors take priority
+        assert_eq!(
+            Err(AllocationError::ZeroLength),
+            WasmAllocation::with_length_checked(Offset::from(8), Length::from(0), max_length),
+        );
+    }
+
+    #[test]
+    pub fn allocation_from_range_test() {
+        assert_eq!(
+            WasmAllocation::new(Offset::from(8), Length::from(4)),
+            WasmAllocation::from_range(8, 12),
+        );
+        assert_eq!(
+            WasmAllocation::new(Offset::from(0), Length::from(std::u32::MAX)),
+            WasmAllocation::from_range(0, std::u32::MAX),
+        );
+
+        // inverted
+        assert_eq!(
+            Err(AllocationError::OutOfBounds),
+            WasmAllocation::from_range(12, 8),
+        );
+
+        // zero width
+        assert_eq!(
+            Err(AllocationError::ZeroLength),
+            WasmAllocation::from_range(8, 8),
+        );
+    }
+
+    #[test]
+    pub fn allocation_new_saturating_test() {
+        // in bounds is unchanged
+        assert_eq!(
+            WasmAllocation::new(Offset::from(1), Length::from(2)),
+            Ok(WasmAllocation::new_saturating(
+                Offset::from(1),
+                Length::from(2)
+            )),
+        );
+
+        // clamp up from zero
+        assert_eq!(
+            WasmAllocation {
+                offset: Offset::from(1),
+                length: Length::from(1),
+            },
+            WasmAllocation::new_saturating(Offset::from(1), Length::from(0)),
+        );
+
+        // clamp down past the max
+        assert_eq!(
+            WasmAllocation {
+                offset: Offset::from(10),
+                length: Length::from(MEMORY_INT_MAX as MemoryInt - 10),
+            },
+            WasmAllocation::new_saturating(Offset::from(10), Length::from(std::u32::MAX)),
+        );
+
+        // no space at all after the offset
+        assert_eq!(
+            WasmAllocation {
+                offset: Offset::from(std::u32::MAX - 1),
+                length: Length::from(1),
+            },
+            WasmAllocation::new_saturating(Offset::from(std::u32::MAX), Length::from(5)),
+        );
+    }
+
+    #[test]
+    pub fn allocation_new_or_empty_test() {
+        assert_eq!(
+            Ok(None),
+            WasmAllocation::new_or_empty(Offset::from(1), Length::from(0)),
+        );
+
+        assert_eq!(
+            Ok(Some(WasmAllocation {
+                offset: Offset::from(1),
+                length: Length::from(2),
+            })),
+            WasmAllocation::new_or_empty(Offset::from(1), Length::from(2)),
+        );
+
+        assert_eq!(
+            Err(AllocationError::OutOfBounds),
+            WasmAllocation::new_or_empty(Offset::from(std::u32::MAX), Length::from(1)),
+        );
+    }
+
+    #[test]
+    pub fn allocation_result_into_ribosome_return_test() {
+        let allocation = WasmAllocation::new(Offset::from(1), Length::from(2)).unwrap();
+        assert_eq!(
+            RibosomeEncodedValue::from(allocation),
+            Ok(allocation).into_ribosome_return(),
+        );
+
+        let result: AllocationResult = Err(AllocationError::OutOfBounds);
+        assert_eq!(
+            RibosomeEncodedValue::Failure(RibosomeErrorCode::OutOfMemory),
+            result.into_ribosome_return(),
+        );
+    }
+
     #[test]
     pub fn allocation_offset_test() {
         assert_eq!(
@@ -260,4 +1344,322 @@ pub mod tests {
         );
     }
 
+    #[test]
+    pub fn align_up_offset_test() {
+        // everything is aligned to 1
+        for i in vec![0, 1, 7, 8, std::u32::MAX] {
+            assert_eq!(Ok(Offset::from(i)), align_up_offset(Offset::from(i), 1));
+        }
+
+        assert_eq!(Ok(Offset::from(0)), align_up_offset(Offset::from(0), 4));
+        assert_eq!(Ok(Offset::from(4)), align_up_offset(Offset::from(1), 4));
+        assert_eq!(Ok(Offset::from(4)), align_up_offset(Offset::from(4), 4));
+        assert_eq!(Ok(Offset::from(8)), align_up_offset(Offset::from(5), 4));
+
+        assert_eq!(Ok(Offset::from(8)), align_up_offset(Offset::from(1), 8));
+        assert_eq!(Ok(Offset::from(8)), align_up_offset(Offset::from(8), 8));
+        assert_eq!(Ok(Offset::from(16)), align_up_offset(Offset::from(9), 8));
+
+        // the largest aligned offset is fine but rounding past it is out of bounds
+        assert_eq!(
+            Ok(Offset::from(std::u32::MAX - 7)),
+            align_up_offset(Offset::from(std::u32::MAX - 7), 8),
+        );
+        assert_eq!(
+            Err(AllocationError::OutOfBounds),
+            align_up_offset(Offset::from(std::u32::MAX - 6), 8),
+        );
+    }
+
+    #[test]
+    pub fn is_aligned_to_test() {
+        let allocation =
+            |offset: MemoryInt| WasmAllocation::new(Offset::from(offset), Length::from(1)).unwrap();
+
+        for aligned in vec![0, 8, 16, 1024] {
+            assert!(allocation(aligned).is_aligned_to(4));
+            assert!(allocation(aligned).is_aligned_to(8));
+        }
+        // aligned to 4 but not 8
+        for offset in vec![4, 12, 1028] {
+            assert!(allocation(offset).is_aligned_to(4));
+            assert!(!allocation(offset).is_aligned_to(8));
+        }
+        for misaligned in vec![1, 2, 3, 5, 7, 9, 1023] {
+            assert!(!allocation(misaligned).is_aligned_to(4));
+            assert!(!allocation(misaligned).is_aligned_to(8));
+        }
+
+        // everything is aligned to 1 and nothing to 0
+        assert!(allocation(7).is_aligned_to(1));
+        assert!(!allocation(0).is_aligned_to(0));
+
+        // rounding up always gives an aligned offset
+        for offset in vec![1, 5, 9, 13] {
+            for align in vec![4, 8] {
+                let aligned = align_up_offset(Offset::from(offset), align).unwrap();
+                assert!(WasmAllocation::new(aligned, Length::from(1))
+                    .unwrap()
+                    .is_aligned_to(align));
+            }
+        }
+    }
+
+    #[test]
+    pub fn align_up_offset_not_power_of_two_test() {
+        for align in vec![0, 3, 6, 12, 100] {
+            assert_eq!(
+                Err(AllocationError::BadStackAlignment),
+                align_up_offset(Offset::from(1), align),
+            );
+        }
+    }
+
+    #[test]
+    pub fn overlapping_region_test() {
+        let allocation = |offset: MemoryInt, length: MemoryInt| {
+            WasmAllocation::new(Offset::from(offset), Length::from(length)).unwrap()
+        };
+
+        // fully contained
+        assert_eq!(
+            Some(allocation(12, 4)),
+            allocation(10, 10).overlapping_region(allocation(12, 4)),
+        );
+        assert_eq!(
+            Some(allocation(12, 4)),
+            allocation(12, 4).overlapping_region(allocation(10, 10)),
+        );
+
+        // partially overlapping
+        assert_eq!(
+            Some(allocation(15, 5)),
+            allocation(10, 10).overlapping_region(allocation(15, 10)),
+        );
+        assert_eq!(
+            Some(allocation(15, 5)),
+            allocation(15, 10).overlapping_region(allocation(10, 10)),
+        );
+
+        // adjacent
+        assert_eq!(
+            None,
+            allocation(10, 10).overlapping_region(allocation(20, 10)),
+        );
+        assert_eq!(
+            None,
+            allocation(20, 10).overlapping_region(allocation(10, 10)),
+        );
+
+        // identical
+        assert_eq!(
+            Some(allocation(10, 10)),
+            allocation(10, 10).overlapping_region(allocation(10, 10)),
+        );
+
+        // at the top of memory
+        assert_eq!(
+            Some(allocation(std::u32::MAX - 1, 1)),
+            allocation(std::u32::MAX - 2, 2).overlapping_region(allocation(std::u32::MAX - 1, 1)),
+        );
+    }
+
+    #[test]
+    pub fn json_allocation_length_test() {
+        assert_eq!(
+            Err(AllocationError::ZeroLength),
+            json_allocation_length(&JsonString::from("")),
+        );
+
+        assert_eq!(
+            Ok(Length::from(13)),
+            json_allocation_length(&JsonString::from("{\"foo\":\"bar\"}")),
+        );
+
+        // bytes not chars
+        assert_eq!(
+            Ok(Length::from(4)),
+            json_allocation_length(&JsonString::from("\"\u{e9}\"")),
+        );
+    }
+
+    #[test]
+    pub fn allocation_le_bytes_test() {
+        let allocation = WasmAllocation::new(Offset::from(0x01020304), Length::from(5)).unwrap();
+        let bytes = allocation.to_le_bytes();
+        assert_eq!([4, 3, 2, 1, 5, 0, 0, 0], bytes);
+        assert_eq!(Ok(allocation), WasmAllocation::from_le_bytes(bytes));
+
+        // zero length
+        assert_eq!(
+            Err(AllocationError::ZeroLength),
+            WasmAllocation::from_le_bytes([4, 3, 2, 1, 0, 0, 0, 0]),
+        );
+        // max offset with non-zero length
+        assert_eq!(
+            Err(AllocationError::OutOfBounds),
+            WasmAllocation::from_le_bytes([255, 255, 255, 255, 1, 0, 0, 0]),
+        );
+    }
+
+    #[test]
+    pub fn allocation_be_bytes_test() {
+        let allocation = WasmAllocation::new(Offset::from(0x01020304), Length::from(5)).unwrap();
+        let bytes = allocation.to_be_bytes();
+        assert_eq!([1, 2, 3, 4, 0, 0, 0, 5], bytes);
+        assert_eq!(Ok(allocation), WasmAllocation::from_be_bytes(bytes));
+
+        assert_eq!(
+            Err(AllocationError::ZeroLength),
+            WasmAllocation::from_be_bytes([1, 2, 3, 4, 0, 0, 0, 0]),
+        );
+    }
+
+    #[test]
+    pub fn union_test() {
+        let allocation = |offset: MemoryInt, length: MemoryInt| {
+            WasmAllocation::new(Offset::from(offset), Length::from(length)).unwrap()
+        };
+
+        // adjacent
+        assert_eq!(
+            allocation(10, 20),
+            allocation(10, 10).union(allocation(20, 10)),
+        );
+        assert_eq!(
+            allocation(10, 20),
+            allocation(20, 10).union(allocation(10, 10)),
+        );
+
+        // gap between
+        assert_eq!(
+            allocation(10, 30),
+            allocation(10, 5).union(allocation(30, 10)),
+        );
+        assert_eq!(
+            allocation(10, 30),
+            allocation(30, 10).union(allocation(10, 5)),
+        );
+
+        // contained
+        assert_eq!(
+            allocation(10, 10),
+            allocation(10, 10).union(allocation(12, 2)),
+        );
+
+        // whole memory
+        assert_eq!(
+            allocation(0, std::u32::MAX),
+            allocation(0, 1).union(allocation(std::u32::MAX - 1, 1)),
+        );
+    }
+
+    #[test]
+    pub fn page_test() {
+        let page = Length::from(U16_MAX + 1);
+
+        // within the first page
+        let first = WasmAllocation::new(Offset::from(0), Length::from(10)).unwrap();
+        assert!(first.is_single_page(page));
+        assert_eq!(Some(0), first.page_index(page));
+
+        // filling the second page exactly
+        let second = WasmAllocation::new(Offset::from(U16_MAX + 1), page).unwrap();
+        assert!(second.is_single_page(page));
+        assert_eq!(Some(1), second.page_index(page));
+
+        // crossing the boundary between the first and second page
+        let crossing = WasmAllocation::new(Offset::from(U16_MAX), Length::from(2)).unwrap();
+        assert!(!crossing.is_single_page(page));
+        assert_eq!(None, crossing.page_index(page));
+
+        // the last byte of memory is in the last page
+        let last = WasmAllocation::new(Offset::from(std::u32::MAX - 1), Length::from(1)).unwrap();
+        assert_eq!(Some(U16_MAX), last.page_index(page));
+
+        assert!(!first.is_single_page(Length::from(0)));
+        assert_eq!(None, first.page_index(Length::from(0)));
+    }
+
+    #[test]
+    pub fn end_offset_test() {
+        assert_eq!(
+            Offset::from(15),
+            WasmAllocation::new(Offset::from(10), Length::from(5))
+                .unwrap()
+                .end_offset(),
+        );
+
+        // ending exactly at the max is the largest possible end
+        let last = WasmAllocation::new(Offset::from(std::u32::MAX - 1), Length::from(1)).unwrap();
+        assert_eq!(Offset::from(std::u32::MAX), last.end_offset());
+        assert_eq!(MEMORY_INT_MAX, MemoryBits::from(last.end_offset()));
+
+        // anything that would end past the max can't be constructed to have an end offset
+        assert_eq!(
+            Err(AllocationError::OutOfBounds),
+            WasmAllocation::new(Offset::from(std::u32::MAX), Length::from(1)),
+        );
+        assert_eq!(
+            Err(AllocationError::OutOfBounds),
+            Offset::from(std::u32::MAX).try_advance(Length::from(1)),
+        );
+    }
+
+    #[test]
+    pub fn cmp_by_length_test() {
+        let allocation = |offset: MemoryInt, length: MemoryInt| {
+            WasmAllocation::new(Offset::from(offset), Length::from(length)).unwrap()
+        };
+
+        let mut allocations = vec![
+            allocation(0, 8),
+            allocation(20, 2),
+            allocation(8, 4),
+            allocation(12, 2),
+            allocation(30, 16),
+        ];
+        allocations.sort_by(WasmAllocation::cmp_by_length);
+
+        assert_eq!(
+            vec![
+                allocation(12, 2),
+                allocation(20, 2),
+                allocation(8, 4),
+                allocation(0, 8),
+                allocation(30, 16),
+            ],
+            allocations,
+        );
+        assert_eq!(
+            std::cmp::Ordering::Equal,
+            allocation(1, 2).cmp_by_length(&allocation(1, 2)),
+        );
+    }
+
+    #[test]
+    pub fn allocation_into_iter_test() {
+        let allocation = WasmAllocation::new(Offset::from(3), Length::from(4)).unwrap();
+
+        let mut addresses = Vec::new();
+        for address in allocation {
+            addresses.push(address);
+        }
+        assert_eq!(vec![3, 4, 5, 6], addresses);
+
+        assert_eq!(
+            1,
+            WasmAllocation::new(Offset::from(0), Length::from(1))
+                .unwrap()
+                .into_iter()
+                .count(),
+        );
+
+        let top = WasmAllocation::new(Offset::from(std::u32::MAX - 1), Length::from(1)).unwrap();
+        assert_eq!(
+            vec![std::u32::MAX as usize - 1],
+            top.into_iter().collect::<Vec<usize>>(),
+        );
+    }
+
 }
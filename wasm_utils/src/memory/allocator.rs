@@ -0,0 +1,424 @@
+use memory::{
+    allocation::{
+        align_up_offset, AllocationError, AllocationResult, Length, Offset, WasmAllocation,
+    },
+    stack::WasmStack,
+    MemoryInt,
+};
+use std::collections::HashMap;
+
+/// one allocation written in chunks on the stack, for data whose length isn't known upfront
+/// e.g. serializing a large iterator item by item
+/// memory is the whole wasm memory that the stack offsets are relative to
+/// the stack is borrowed for the whole write so the chunks are always contiguous
+#[derive(Debug)]
+pub struct GrowableAllocation<'a> {
+    stack: &'a mut WasmStack,
+    memory: &'a mut [u8],
+    start: Option<Offset>,
+    end: Offset,
+}
+
+impl<'a> GrowableAllocation<'a> {
+    pub fn new(stack: &'a mut WasmStack, memory: &'a mut [u8]) -> GrowableAllocation<'a> {
+        let top = MemoryInt::from(stack.top());
+        GrowableAllocation {
+            stack,
+            memory,
+            start: None,
+            end: Offset::from(top),
+        }
+    }
+
+    /// allocates the next chunk on the stack and copies bytes into it
+    /// empty bytes are a no-op, a failed write doesn't use any of the stack
+    /// OutOfBounds if the chunk extends past the end of memory
+    pub fn write(&mut self, bytes: &[u8]) -> Result<(), AllocationError> {
+        if bytes.is_empty() {
+            return Ok(());
+        }
+        let chunk = self
+            .stack
+            .next_allocation(Length::try_from_usize(bytes.len())?)?;
+        chunk.read_slice(self.memory)?;
+        self.stack.allocate(chunk)?;
+
+        let start = MemoryInt::from(chunk.offset()) as usize;
+        self.memory[start..start + bytes.len()].copy_from_slice(bytes);
+
+        if self.start.is_none() {
+            self.start = Some(chunk.offset());
+        }
+        self.end = chunk.end_offset();
+        Ok(())
+    }
+
+    /// the single allocation covering every chunk written
+    /// ZeroLength if nothing was written
+    pub fn finish(self) -> AllocationResult {
+        match self.start {
+            None => Err(AllocationError::ZeroLength),
+            Some(start) => WasmAllocation::new(
+                start,
+                Length::from(MemoryInt::from(self.end) - MemoryInt::from(start)),
+            ),
+        }
+    }
+}
+
+/// hands out consecutive allocations between start and end for wasm host environments
+/// e.g. a test harness or conductor writing into guest memory
+/// individual allocations are never freed, reset() frees everything at once
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct BumpAllocator {
+    start: Offset,
+    end: Offset,
+    next: Offset,
+    align: MemoryInt,
+}
+
+impl BumpAllocator {
+    /// byte aligned allocations in [start, end)
+    pub fn new(start: Offset, end: Offset) -> BumpAllocator {
+        BumpAllocator {
+            start,
+            end,
+            next: start,
+            align: 1,
+        }
+    }
+
+    /// every allocation offset will be rounded up to a multiple of align
+    /// align must be a power of two, otherwise alloc() fails with BadStackAlignment
+    pub fn with_align(self, align: MemoryInt) -> BumpAllocator {
+        BumpAllocator { align, ..self }
+    }
+
+    /// the next length bytes after any padding for alignment
+    /// OutOfBounds if the allocation would extend past end
+    /// a failed allocation does not use any space
+    pub fn alloc(&mut self, length: Length) -> AllocationResult {
+        let offset = align_up_offset(self.next, self.align)?;
+        let allocation = WasmAllocation::new(offset, length)?;
+        let next = offset.try_advance(length)?;
+        if MemoryInt::from(next) > MemoryInt::from(self.end) {
+            Err(AllocationError::OutOfBounds)
+        } else {
+            self.next = next;
+            Ok(allocation)
+        }
+    }
+
+    /// frees every allocation, the next alloc() starts from start again
+    pub fn reset(&mut self) {
+        self.next = self.start;
+    }
+
+    /// bytes used so far including any padding for alignment
+    pub fn used(&self) -> MemoryInt {
+        MemoryInt::from(self.next) - MemoryInt::from(self.start)
+    }
+
+    /// bytes left before end, ignoring any padding the next allocation may need
+    pub fn remaining(&self) -> MemoryInt {
+        MemoryInt::from(self.end).saturating_sub(MemoryInt::from(self.next))
+    }
+}
+
+/// reuses released allocations of the same length before growing the stack
+/// e.g. for repeatedly allocating and freeing same sized regions
+/// released allocations are kept in free lists by length and never merged or split
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct WasmAllocationPool {
+    stack: WasmStack,
+    free: HashMap<MemoryInt, Vec<WasmAllocation>>,
+}
+
+impl WasmAllocationPool {
+    pub fn new(stack: WasmStack) -> WasmAllocationPool {
+        WasmAllocationPool {
+            stack,
+            free: HashMap::new(),
+        }
+    }
+
+    /// a released allocation of exactly length if there is one, otherwise the next allocation
+    /// on the stack
+    pub fn acquire(&mut self, length: Length) -> AllocationResult {
+        match self
+            .free
+            .get_mut(&MemoryInt::from(length))
+            .and_then(|free| free.pop())
+        {
+            Some(allocation) => Ok(allocation),
+            None => {
+                let allocation = self.stack.next_allocation(length)?;
+                self.stack.allocate(allocation)?;
+                Ok(allocation)
+            }
+        }
+    }
+
+    /// makes the allocation available to acquire() again
+    /// the stack is not deallocated so the region stays reserved for the pool
+    /// OutOfBounds if the allocation ends above the top of the stack so can't be from the pool
+    /// Corrupt if the allocation was already released, acquire() would hand it out twice
+    pub fn release(&mut self, allocation: WasmAllocation) -> Result<(), AllocationError> {
+        if MemoryInt::from(allocation.end_offset()) > MemoryInt::from(self.stack.top()) {
+            return Err(AllocationError::OutOfBounds);
+        }
+        let free = self
+            .free
+            .entry(MemoryInt::from(allocation.length()))
+            .or_insert_with(Vec::new);
+        if free.contains(&allocation) {
+            return Err(AllocationError::Corrupt {
+                reason: String::from("allocation released twice"),
+            });
+        }
+        free.push(allocation);
+        Ok(())
+    }
+
+    pub fn stack(&self) -> WasmStack {
+        self.stack
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+
+    use memory::{
+        allocation::{AllocationError, Length, Offset, WasmAllocation},
+        allocator::{BumpAllocator, GrowableAllocation, WasmAllocationPool},
+        stack::{Top, WasmStack},
+    };
+
+    #[test]
+    fn growable_allocation_test() {
+        let mut stack = WasmStack::new();
+        stack
+            .allocate(stack.next_allocation(Length::from(2)).unwrap())
+            .unwrap();
+        let mut memory = vec![0_u8; 16];
+
+        let mut growable = GrowableAllocation::new(&mut stack, &mut memory);
+        assert_eq!(Ok(()), growable.write(b"foo"));
+        assert_eq!(Ok(()), growable.write(b""));
+        assert_eq!(Ok(()), growable.write(b"ba"));
+        assert_eq!(Ok(()), growable.write(b"rbaz"));
+
+        let allocation = growable.finish().unwrap();
+        assert_eq!(
+            WasmAllocation::new(Offset::from(2), Length::from(9)),
+            Ok(allocation),
+        );
+        assert_eq!(Ok(&b"foobarbaz"[..]), allocation.read_slice(&memory));
+        assert_eq!(Top(11), stack.top());
+    }
+
+    #[test]
+    fn growable_allocation_err_test() {
+        let mut stack = WasmStack::new();
+        let mut memory = vec![0_u8; 4];
+
+        assert_eq!(
+            Err(AllocationError::ZeroLength),
+            GrowableAllocation::new(&mut stack, &mut memory).finish(),
+        );
+
+        let mut growable = GrowableAllocation::new(&mut stack, &mut memory);
+        assert_eq!(Ok(()), growable.write(b"foo"));
+        // past the end of memory
+        assert_eq!(Err(AllocationError::OutOfBounds), growable.write(b"ba"));
+        assert_eq!(
+            WasmAllocation::new(Offset::from(0), Length::from(3)),
+            growable.finish(),
+        );
+        // the failed write didn't use any of the stack
+        assert_eq!(Top(3), stack.top());
+    }
+
+    #[test]
+    fn bump_allocator_alloc_test() {
+        let mut allocator = BumpAllocator::new(Offset::from(10), Offset::from(20));
+        assert_eq!(0, allocator.used());
+        assert_eq!(10, allocator.remaining());
+
+        assert_eq!(
+            WasmAllocation::new(Offset::from(10), Length::from(3)),
+            allocator.alloc(Length::from(3)),
+        );
+        assert_eq!(
+            WasmAllocation::new(Offset::from(13), Length::from(5)),
+            allocator.alloc(Length::from(5)),
+        );
+        assert_eq!(8, allocator.used());
+        assert_eq!(2, allocator.remaining());
+
+        // too big for the remaining space doesn't use any of it
+        assert_eq!(
+            Err(AllocationError::OutOfBounds),
+            allocator.alloc(Length::from(3)),
+        );
+        assert_eq!(8, allocator.used());
+
+        assert_eq!(
+            Err(AllocationError::ZeroLength),
+            allocator.alloc(Length::from(0)),
+        );
+
+        // exactly fills to end
+        assert_eq!(
+            WasmAllocation::new(Offset::from(18), Length::from(2)),
+            allocator.alloc(Length::from(2)),
+        );
+        assert_eq!(0, allocator.remaining());
+        assert_eq!(
+            Err(AllocationError::OutOfBounds),
+            allocator.alloc(Length::from(1)),
+        );
+    }
+
+    #[test]
+    fn bump_allocator_align_test() {
+        let mut allocator = BumpAllocator::new(Offset::from(1), Offset::from(32)).with_align(8);
+
+        assert_eq!(
+            WasmAllocation::new(Offset::from(8), Length::from(3)),
+            allocator.alloc(Length::from(3)),
+        );
+        assert_eq!(
+            WasmAllocation::new(Offset::from(16), Length::from(8)),
+            allocator.alloc(Length::from(8)),
+        );
+        assert_eq!(23, allocator.used());
+
+        assert_eq!(
+            Err(AllocationError::OutOfBounds),
+            allocator.alloc(Length::from(9)),
+        );
+        assert_eq!(
+            WasmAllocation::new(Offset::from(24), Length::from(8)),
+            allocator.alloc(Length::from(8)),
+        );
+
+        let mut allocator = BumpAllocator::new(Offset::from(0), Offset::from(32)).with_align(3);
+        assert_eq!(
+            Err(AllocationError::BadStackAlignment),
+            allocator.alloc(Length::from(1)),
+        );
+    }
+
+    #[test]
+    fn bump_allocator_reset_test() {
+        let mut allocator = BumpAllocator::new(Offset::from(0), Offset::from(10));
+        allocator.alloc(Length::from(6)).unwrap();
+        assert_eq!(
+            Err(AllocationError::OutOfBounds),
+            allocator.alloc(Length::from(6)),
+        );
+
+        allocator.reset();
+        assert_eq!(0, allocator.used());
+        assert_eq!(10, allocator.remaining());
+        assert_eq!(
+            WasmAllocation::new(Offset::from(0), Length::from(6)),
+            allocator.alloc(Length::from(6)),
+        );
+    }
+
+    #[test]
+    fn pool_reuses_released_allocation_test() {
+        let mut pool = WasmAllocationPool::new(WasmStack::new());
+
+        let first = pool.acquire(Length::from(4)).unwrap();
+        let second = pool.acquire(Length::from(4)).unwrap();
+        assert_eq!(
+            WasmAllocation::new(Offset::from(0), Length::from(4)),
+            Ok(first)
+        );
+        assert_eq!(
+            WasmAllocation::new(Offset::from(4), Length::from(4)),
+            Ok(second)
+        );
+        assert_eq!(Top(8), pool.stack().top());
+
+        assert_eq!(Ok(()), pool.release(first));
+        assert_eq!(Ok(first), pool.acquire(Length::from(4)));
+        // the stack didn't move
+        assert_eq!(Top(8), pool.stack().top());
+
+        // the free list is empty again so the stack grows
+        assert_eq!(
+            WasmAllocation::new(Offset::from(8), Length::from(4)),
+            pool.acquire(Length::from(4)),
+        );
+        assert_eq!(Top(12), pool.stack().top());
+    }
+
+    #[test]
+    fn pool_falls_through_to_stack_test() {
+        let mut pool = WasmAllocationPool::new(WasmStack::new());
+
+        let allocation = pool.acquire(Length::from(4)).unwrap();
+        pool.release(allocation).unwrap();
+
+        // a novel length doesn't reuse the released allocation
+        assert_eq!(
+            WasmAllocation::new(Offset::from(4), Length::from(2)),
+            pool.acquire(Length::from(2)),
+        );
+        assert_eq!(Top(6), pool.stack().top());
+
+        assert_eq!(
+            Err(AllocationError::ZeroLength),
+            pool.acquire(Length::from(0)),
+        );
+        assert_eq!(Top(6), pool.stack().top());
+    }
+
+    #[test]
+    fn pool_double_release_test() {
+        let mut pool = WasmAllocationPool::new(WasmStack::new());
+
+        let allocation = pool.acquire(Length::from(4)).unwrap();
+        assert_eq!(Ok(()), pool.release(allocation));
+        assert_eq!(
+            Err(AllocationError::Corrupt {
+                reason: String::from("allocation released twice"),
+            }),
+            pool.release(allocation),
+        );
+
+        // the region is only handed out once
+        assert_eq!(Ok(allocation), pool.acquire(Length::from(4)));
+        assert_eq!(
+            WasmAllocation::new(Offset::from(4), Length::from(4)),
+            pool.acquire(Length::from(4)),
+        );
+    }
+
+    #[test]
+    fn pool_release_foreign_allocation_test() {
+        let mut pool = WasmAllocationPool::new(WasmStack::new());
+        pool.acquire(Length::from(4)).unwrap();
+
+        // above the top of the stack so never acquired from this pool
+        assert_eq!(
+            Err(AllocationError::OutOfBounds),
+            pool.release(WasmAllocation::new(Offset::from(2), Length::from(4)).unwrap()),
+        );
+        assert_eq!(
+            Err(AllocationError::OutOfBounds),
+            pool.release(WasmAllocation::new(Offset::from(8), Length::from(4)).unwrap()),
+        );
+
+        // the stack still grows as before
+        assert_eq!(
+            WasmAllocation::new(Offset::from(4), Length::from(4)),
+            pool.acquire(Length::from(4)),
+        );
+    }
+
+}
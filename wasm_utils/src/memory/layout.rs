@@ -0,0 +1,192 @@
+use holochain_core_types::error::HolochainError;
+use memory::allocation::{AllocationError, WasmAllocation};
+use std::{error::Error, fmt};
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum MemoryLayoutError {
+    /// the layout already has a section with this name
+    DuplicateSection(String),
+    /// ReservedRegion if the allocation overlaps an existing section
+    Allocation(AllocationError),
+}
+
+impl fmt::Display for MemoryLayoutError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MemoryLayoutError::DuplicateSection(name) => {
+                write!(f, "Memory layout already has a section named {}", name)
+            }
+            MemoryLayoutError::Allocation(allocation_error) => write!(f, "{}", allocation_error),
+        }
+    }
+}
+
+impl Error for MemoryLayoutError {}
+
+impl From<AllocationError> for MemoryLayoutError {
+    fn from(allocation_error: AllocationError) -> Self {
+        MemoryLayoutError::Allocation(allocation_error)
+    }
+}
+
+impl From<MemoryLayoutError> for HolochainError {
+    fn from(memory_layout_error: MemoryLayoutError) -> Self {
+        HolochainError::ErrorGeneric(memory_layout_error.to_string())
+    }
+}
+
+/// named sections of wasm memory e.g. code, data, heap and stack
+/// describes the memory map of a compiled zome for the DNA bundler
+/// sections never overlap and are kept in the order they were added
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MemoryLayout {
+    sections: Vec<(String, WasmAllocation)>,
+}
+
+impl MemoryLayout {
+    pub fn new() -> MemoryLayout {
+        MemoryLayout {
+            sections: Vec::new(),
+        }
+    }
+
+    /// the allocation of the section with the given name, if any
+    pub fn section(&self, name: &str) -> Option<WasmAllocation> {
+        self.sections
+            .iter()
+            .find(|(section_name, _)| section_name == name)
+            .map(|(_, allocation)| *allocation)
+    }
+
+    /// adds a named section to the layout
+    /// DuplicateSection if the name is already used, ReservedRegion if the allocation overlaps
+    /// an existing section, the layout is unchanged on error
+    pub fn add_section(
+        &mut self,
+        name: &str,
+        alloc: WasmAllocation,
+    ) -> Result<(), MemoryLayoutError> {
+        if self.section(name).is_some() {
+            Err(MemoryLayoutError::DuplicateSection(name.to_string()))
+        } else if self
+            .sections
+            .iter()
+            .any(|(_, allocation)| allocation.overlapping_region(alloc).is_some())
+        {
+            Err(MemoryLayoutError::from(AllocationError::ReservedRegion))
+        } else {
+            self.sections.push((name.to_string(), alloc));
+            Ok(())
+        }
+    }
+
+    /// every (name, allocation) in the order the sections were added
+    pub fn sections(&self) -> impl Iterator<Item = (&str, WasmAllocation)> {
+        self.sections
+            .iter()
+            .map(|(name, allocation)| (name.as_str(), *allocation))
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+
+    use holochain_core_types::error::HolochainError;
+    use memory::{
+        allocation::{AllocationError, Length, Offset, WasmAllocation},
+        layout::{MemoryLayout, MemoryLayoutError},
+        MemoryInt,
+    };
+
+    fn allocation(offset: MemoryInt, length: MemoryInt) -> WasmAllocation {
+        WasmAllocation::new(Offset::from(offset), Length::from(length)).unwrap()
+    }
+
+    #[test]
+    fn memory_layout_new_test() {
+        assert_eq!(MemoryLayout::default(), MemoryLayout::new());
+        assert_eq!(None, MemoryLayout::new().section("code"));
+        assert_eq!(0, MemoryLayout::new().sections().count());
+    }
+
+    #[test]
+    fn memory_layout_add_section_test() {
+        let mut layout = MemoryLayout::new();
+
+        assert_eq!(Ok(()), layout.add_section("code", allocation(0, 10)));
+        // adjacent sections don't overlap
+        assert_eq!(Ok(()), layout.add_section("data", allocation(10, 10)));
+        assert_eq!(Ok(()), layout.add_section("stack", allocation(100, 50)));
+        // gaps between sections are fine
+        assert_eq!(Ok(()), layout.add_section("heap", allocation(30, 20)));
+
+        assert_eq!(Some(allocation(0, 10)), layout.section("code"));
+        assert_eq!(Some(allocation(10, 10)), layout.section("data"));
+        assert_eq!(Some(allocation(30, 20)), layout.section("heap"));
+        assert_eq!(Some(allocation(100, 50)), layout.section("stack"));
+        assert_eq!(None, layout.section("foo"));
+
+        assert_eq!(
+            vec![
+                ("code", allocation(0, 10)),
+                ("data", allocation(10, 10)),
+                ("stack", allocation(100, 50)),
+                ("heap", allocation(30, 20)),
+            ],
+            layout.sections().collect::<Vec<(&str, WasmAllocation)>>(),
+        );
+    }
+
+    #[test]
+    fn memory_layout_add_section_err_test() {
+        let mut layout = MemoryLayout::new();
+        layout.add_section("data", allocation(10, 10)).unwrap();
+        let before = layout.clone();
+
+        // overlapping the start, the end, inside and around an existing section
+        for overlapping in vec![
+            allocation(5, 6),
+            allocation(19, 5),
+            allocation(12, 2),
+            allocation(0, 100),
+        ] {
+            assert_eq!(
+                Err(MemoryLayoutError::Allocation(
+                    AllocationError::ReservedRegion
+                )),
+                layout.add_section("heap", overlapping),
+            );
+        }
+
+        // names are unique even when the allocation doesn't overlap
+        assert_eq!(
+            Err(MemoryLayoutError::DuplicateSection(String::from("data"))),
+            layout.add_section("data", allocation(50, 10)),
+        );
+        // a duplicate name is reported before an overlap
+        assert_eq!(
+            Err(MemoryLayoutError::DuplicateSection(String::from("data"))),
+            layout.add_section("data", allocation(10, 10)),
+        );
+
+        assert_eq!(before, layout);
+    }
+
+    #[test]
+    fn memory_layout_error_test() {
+        assert_eq!(
+            "Memory layout already has a section named data",
+            MemoryLayoutError::DuplicateSection(String::from("data")).to_string(),
+        );
+        assert_eq!(
+            AllocationError::ReservedRegion.to_string(),
+            MemoryLayoutError::from(AllocationError::ReservedRegion).to_string(),
+        );
+        assert_eq!(
+            HolochainError::ErrorGeneric(String::from(
+                "Memory layout already has a section named data"
+            )),
+            HolochainError::from(MemoryLayoutError::DuplicateSection(String::from("data"))),
+        );
+    }
+}
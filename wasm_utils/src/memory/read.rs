@@ -1,5 +1,15 @@
-use crate::memory::{allocation::WasmAllocation, MemoryInt};
-use std::{ffi::CStr, os::raw::c_char};
+use crate::memory::{
+    allocation::{AllocationError, WasmAllocation},
+    MemoryInt,
+};
+use holochain_core_types::{
+    cas::content::{Address, AddressableContent},
+    error::{HolochainError, RibosomeErrorCode},
+    json::JsonString,
+};
+#[cfg(any(test, feature = "debug"))]
+use std::fmt::Write;
+use std::{convert::TryFrom, ffi::CStr, os::raw::c_char};
 
 /// reads are always from a WasmAllocation
 impl WasmAllocation {
@@ -13,13 +23,107 @@ impl WasmAllocation {
     pub fn read_to_string(&self) -> String {
         WasmAllocation::read_str_raw(MemoryInt::from(self.offset()) as *mut c_char).to_string()
     }
+
+    /// the bytes of memory within the allocation
+    /// memory is the whole wasm memory that the allocation offset is relative to
+    /// OutOfBounds if the allocation extends past the end of memory
+    pub fn read_slice(self, memory: &[u8]) -> Result<&[u8], AllocationError> {
+        let start = MemoryInt::from(self.offset()) as usize;
+        let end = start + usize::from(self.length());
+        if end > memory.len() {
+            Err(AllocationError::OutOfBounds)
+        } else {
+            Ok(&memory[start..end])
+        }
+    }
+
+    /// the JSON string within the allocation of memory
+    /// OutOfBounds if the allocation extends past the end of memory
+    /// Serialization if the bytes are not valid UTF-8
+    pub fn read_json_string(self, memory: &[u8]) -> Result<JsonString, AllocationError> {
+        let bytes = self.read_slice(memory)?;
+        String::from_utf8(bytes.to_vec())
+            .map(JsonString::from)
+            .map_err(|_| AllocationError::Serialization)
+    }
+
+    /// the content address of the JSON within the allocation of memory
+    /// the same Address as AddressableContent gives the JsonString, i.e. its SHA-256 hash
+    /// errors as per read_json_string
+    pub fn address(self, memory: &[u8]) -> Result<Address, AllocationError> {
+        Ok(self.read_json_string(memory)?.address())
+    }
+
+    /// classic hex + ASCII dump of the bytes of memory within the allocation, 16 bytes per line
+    /// addresses are absolute in memory rather than relative to the allocation
+    /// e.g.
+    /// 00000004  68 65 6c 6c 6f 20 77 6f  72 6c 64 0a              |hello world.|
+    #[cfg(any(test, feature = "debug"))]
+    pub fn debug_hexdump(self, memory: &[u8]) -> Result<String, AllocationError> {
+        let offset = MemoryInt::from(self.offset()) as usize;
+        let mut dump = String::new();
+        for (line, bytes) in self.read_slice(memory)?.chunks(16).enumerate() {
+            // writing to a String can't fail
+            write!(dump, "{:08x}  ", offset + line * 16).unwrap();
+            for i in 0..16 {
+                match bytes.get(i) {
+                    Some(byte) => write!(dump, "{:02x} ", byte).unwrap(),
+                    None => dump.push_str("   "),
+                }
+                if i == 7 {
+                    dump.push(' ');
+                }
+            }
+            dump.push_str(" |");
+            for byte in bytes {
+                dump.push(match byte {
+                    0x20..=0x7e => *byte as char,
+                    _ => '.',
+                });
+            }
+            dump.push_str("|\n");
+        }
+        Ok(dump)
+    }
+}
+
+/// reads and deserializes the JSON within the allocation of memory
+/// the read counterpart to WasmStack::write_json for hosts reading from a memory slice
+/// anything other than an out of bounds allocation is ArgumentDeserializationFailed
+pub fn read_json<T: TryFrom<JsonString>>(
+    memory: &[u8],
+    allocation: WasmAllocation,
+) -> Result<T, HolochainError> {
+    let json = allocation
+        .read_json_string(memory)
+        .map_err(|allocation_error| match allocation_error {
+            AllocationError::Serialization => {
+                HolochainError::Ribosome(RibosomeErrorCode::ArgumentDeserializationFailed)
+            }
+            _ => HolochainError::from(allocation_error),
+        })?;
+    T::try_from(json)
+        .map_err(|_| HolochainError::Ribosome(RibosomeErrorCode::ArgumentDeserializationFailed))
 }
 
 #[cfg(test)]
 pub mod tests {
 
-    use memory::allocation::WasmAllocation;
-    use std::{ffi::CString, os::raw::c_char};
+    use holochain_core_types::{
+        cas::content::AddressableContent,
+        error::{HolochainError, RibosomeErrorCode},
+        json::JsonString,
+    };
+    use memory::{
+        allocation::{AllocationError, Length, Offset, WasmAllocation},
+        read::read_json,
+    };
+    use std::{convert::TryFrom, ffi::CString, os::raw::c_char};
+
+    #[derive(Serialize, Deserialize, Debug, DefaultJson, PartialEq)]
+    struct Foo {
+        foo: String,
+    }
 
     #[test]
     pub fn read_str_raw_test() {
@@ -30,4 +134,217 @@ pub mod tests {
         assert_eq!(s, WasmAllocation::read_str_raw(ptr as *mut c_char),);
     }
 
+    #[test]
+    pub fn read_slice_test() {
+        let memory = b"foobarbaz";
+
+        assert_eq!(
+            Ok(&b"bar"[..]),
+            WasmAllocation::new(Offset::from(3), Length::from(3))
+                .unwrap()
+                .read_slice(memory),
+        );
+        assert_eq!(
+            Ok(&b"baz"[..]),
+            WasmAllocation::new(Offset::from(6), Length::from(3))
+                .unwrap()
+                .read_slice(memory),
+        );
+        assert_eq!(
+            Err(AllocationError::OutOfBounds),
+            WasmAllocation::new(Offset::from(6), Length::from(4))
+                .unwrap()
+                .read_slice(memory),
+        );
+    }
+
+    #[test]
+    pub fn debug_hexdump_test() {
+        let mut memory = vec![0_u8; 4];
+        memory.extend_from_slice(b"hello world\n0123456789abcdef");
+
+        assert_eq!(
+            "00000004  68 65 6c 6c 6f 20 77 6f  72 6c 64 0a              |hello world.|\n",
+            WasmAllocation::new(Offset::from(4), Length::from(12))
+                .unwrap()
+                .debug_hexdump(&memory)
+                .unwrap(),
+        );
+
+        assert_eq!(
+            concat!(
+                "00000000  00 00 00 00 68 65 6c 6c  6f 20 77 6f 72 6c 64 0a  |....hello world.|\n",
+                "00000010  30 31                                             |01|\n",
+            ),
+            WasmAllocation::new(Offset::from(0), Length::from(18))
+                .unwrap()
+                .debug_hexdump(&memory)
+                .unwrap(),
+        );
+
+        assert_eq!(
+            Err(AllocationError::OutOfBounds),
+            WasmAllocation::new(Offset::from(30), Length::from(10))
+                .unwrap()
+                .debug_hexdump(&memory),
+        );
+    }
+
+    #[test]
+    pub fn read_json_round_trip_test() {
+        let foo = Foo {
+            foo: "bar".to_string(),
+        };
+        let json = JsonString::from(&foo);
+
+        // the json is written after some unrelated bytes in memory
+        let mut memory = vec![1_u8; 8];
+        memory.extend_from_slice(&json.clone().into_bytes());
+        memory.extend_from_slice(&[2, 3]);
+        let allocation = WasmAllocation::new(
+            Offset::from(8),
+            Length::from(json.into_bytes().len() as u32),
+        )
+        .unwrap();
+
+        assert_eq!(Ok(foo), read_json::<Foo>(&memory, allocation));
+
+        // not the json
+        assert_eq!(
+            Err(HolochainError::Ribosome(
+                RibosomeErrorCode::ArgumentDeserializationFailed
+            )),
+            read_json::<Foo>(
+                &memory,
+                WasmAllocation::new(Offset::from(0), Length::from(8)).unwrap()
+            ),
+        );
+
+        assert_eq!(
+            Err(HolochainError::from(AllocationError::OutOfBounds)),
+            read_json::<Foo>(
+                &memory,
+                WasmAllocation::new(Offset::from(8), Length::from(100)).unwrap()
+            ),
+        );
+
+        // not UTF-8
+        assert_eq!(
+            Err(HolochainError::Ribosome(
+                RibosomeErrorCode::ArgumentDeserializationFailed
+            )),
+            read_json::<Foo>(
+                &[0xff, 0xfe],
+                WasmAllocation::new(Offset::from(0), Length::from(2)).unwrap()
+            ),
+        );
+    }
+
+    #[test]
+    pub fn read_json_string_test() {
+        let memory = b"xx{\"foo\":\"bar\"}yy";
+        let allocation = WasmAllocation::new(Offset::from(2), Length::from(13)).unwrap();
+
+        assert_eq!(
+            Ok(JsonString::from("{\"foo\":\"bar\"}")),
+            allocation.read_json_string(memory),
+        );
+
+        assert_eq!(
+            Err(AllocationError::OutOfBounds),
+            WasmAllocation::new(Offset::from(2), Length::from(100))
+                .unwrap()
+                .read_json_string(memory),
+        );
+
+        assert_eq!(
+            Err(AllocationError::Serialization),
+            WasmAllocation::new(Offset::from(0), Length::from(2))
+                .unwrap()
+                .read_json_string(&[0xff, 0xfe]),
+        );
+    }
+
+    #[test]
+    pub fn address_test() {
+        let memory = b"xx{\"foo\":\"bar\"}yy";
+        let allocation = WasmAllocation::new(Offset::from(2), Length::from(13)).unwrap();
+
+        assert_eq!(
+            Ok(JsonString::from("{\"foo\":\"bar\"}").address()),
+            allocation.address(memory),
+        );
+        // different bytes are a different address
+        assert_ne!(
+            allocation.address(memory),
+            WasmAllocation::new(Offset::from(1), Length::from(14))
+                .unwrap()
+                .address(memory),
+        );
+
+        assert_eq!(
+            Err(AllocationError::OutOfBounds),
+            WasmAllocation::new(Offset::from(2), Length::from(100))
+                .unwrap()
+                .address(memory),
+        );
+    }
+
+    #[test]
+    pub fn write_json_string_round_trip_test() {
+        let json = JsonString::from("{\"foo\":\"bar\"}");
+        let mut memory = vec![0_u8; 20];
+        let allocation = WasmAllocation::new(Offset::from(4), Length::from(13)).unwrap();
+
+        assert_eq!(Ok(()), allocation.write_json_string(&mut memory, &json));
+        assert_eq!(Ok(json.clone()), allocation.read_json_string(&memory));
+        // memory outside the allocation is untouched
+        assert_eq!(&[0_u8; 4], &memory[..4]);
+        assert_eq!(&[0_u8; 3], &memory[17..]);
+
+        // the json must fill the allocation exactly
+        assert_eq!(
+            Err(AllocationError::OutOfBounds),
+            WasmAllocation::new(Offset::from(4), Length::from(12))
+                .unwrap()
+                .write_json_string(&mut memory, &json),
+        );
+        assert_eq!(
+            Err(AllocationError::OutOfBounds),
+            WasmAllocation::new(Offset::from(4), Length::from(14))
+                .unwrap()
+                .write_json_string(&mut memory, &json),
+        );
+
+        // the allocation must fit in memory
+        assert_eq!(
+            Err(AllocationError::OutOfBounds),
+            WasmAllocation::new(Offset::from(10), Length::from(13))
+                .unwrap()
+                .write_json_string(&mut memory, &json),
+        );
+    }
+
+    #[test]
+    pub fn zero_test() {
+        let mut memory = b"foobarbaz".to_vec();
+
+        assert_eq!(
+            Ok(()),
+            WasmAllocation::new(Offset::from(3), Length::from(3))
+                .unwrap()
+                .zero(&mut memory),
+        );
+        assert_eq!(b"foo\0\0\0baz".to_vec(), memory);
+
+        // out of bounds zeroes nothing
+        assert_eq!(
+            Err(AllocationError::OutOfBounds),
+            WasmAllocation::new(Offset::from(6), Length::from(4))
+                .unwrap()
+                .zero(&mut memory),
+        );
+        assert_eq!(b"foo\0\0\0baz".to_vec(), memory);
+    }
+
 }
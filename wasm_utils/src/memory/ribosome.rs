@@ -1,17 +1,16 @@
 // extends memory allocation to work with ribosome encodings
 
 use holochain_core_types::{
-    bits_n_pieces::{u64_merge_bits, u64_split_bits},
+    bits_n_pieces::u64_merge_bits,
     error::{
-        HolochainError, RibosomeEncodedAllocation, RibosomeEncodedValue, RibosomeEncodingBits,
-        RibosomeErrorCode,
+        HolochainError, IntoRibosomeErrorCode, RibosomeEncodedAllocation, RibosomeEncodedValue,
+        RibosomeEncodingBits, RibosomeErrorCode, RibosomeRuntimeBits,
     },
     json::JsonString,
 };
 use memory::{
     allocation::{AllocationError, AllocationResult, WasmAllocation},
     stack::WasmStack,
-    MemoryBits,
 };
 use std::convert::TryFrom;
 
@@ -20,8 +19,10 @@ impl TryFrom<RibosomeEncodedAllocation> for WasmAllocation {
     fn try_from(
         ribosome_memory_allocation: RibosomeEncodedAllocation,
     ) -> Result<Self, Self::Error> {
-        let (offset, length) = u64_split_bits(MemoryBits::from(ribosome_memory_allocation));
-        WasmAllocation::new(offset.into(), length.into())
+        WasmAllocation::new(
+            ribosome_memory_allocation.offset().into(),
+            ribosome_memory_allocation.length().into(),
+        )
     }
 }
 
@@ -41,6 +42,16 @@ impl From<WasmAllocation> for RibosomeEncodedValue {
     }
 }
 
+/// maps to HolochainError rather than AllocationError so that Failure codes are preserved
+impl TryFrom<RibosomeEncodedValue> for WasmAllocation {
+    type Error = HolochainError;
+    fn try_from(ribosome_encoded_value: RibosomeEncodedValue) -> Result<Self, Self::Error> {
+        Ok(WasmAllocation::try_from(
+            ribosome_encoded_value.allocation_or_err()?,
+        )?)
+    }
+}
+
 impl From<AllocationError> for RibosomeErrorCode {
     fn from(allocation_error: AllocationError) -> Self {
         match allocation_error {
@@ -48,10 +59,20 @@ impl From<AllocationError> for RibosomeErrorCode {
             AllocationError::ZeroLength => RibosomeErrorCode::ZeroSizedAllocation,
             AllocationError::BadStackAlignment => RibosomeErrorCode::NotAnAllocation,
             AllocationError::Serialization => RibosomeErrorCode::NotAnAllocation,
+            AllocationError::StackOverflow { .. } => RibosomeErrorCode::OutOfMemory,
+            AllocationError::ReservedRegion => RibosomeErrorCode::NotAnAllocation,
+            AllocationError::Corrupt { .. } => RibosomeErrorCode::Unspecified,
         }
     }
 }
 
+/// @see From<AllocationError> for RibosomeErrorCode
+impl IntoRibosomeErrorCode for AllocationError {
+    fn ribosome_code(&self) -> RibosomeErrorCode {
+        RibosomeErrorCode::from(self.clone())
+    }
+}
+
 impl From<AllocationError> for RibosomeEncodedValue {
     fn from(allocation_error: AllocationError) -> Self {
         RibosomeEncodedValue::Failure(RibosomeErrorCode::from(allocation_error))
@@ -59,15 +80,35 @@ impl From<AllocationError> for RibosomeEncodedValue {
 }
 
 impl AllocationError {
+    /// the raw bits to return to the wasm guest for this error in a single step
+    /// AllocationError -> RibosomeErrorCode -> RibosomeEncodedValue::Failure -> bits
+    /// @see From<AllocationError> for RibosomeErrorCode for the mapping of each error
+    /// e.g. Err(allocation_error) => return allocation_error.as_ribosome_encoding()
     pub fn as_ribosome_encoding(&self) -> RibosomeEncodingBits {
         RibosomeEncodedValue::from(self.clone()).into()
     }
 }
 
+/// the canonical safe decoding of raw bits into an allocation
+/// @see WasmAllocation::try_from_ribosome_encoding
+impl TryFrom<RibosomeEncodingBits> for WasmAllocation {
+    type Error = AllocationError;
+    fn try_from(encoded_value: RibosomeEncodingBits) -> Result<Self, Self::Error> {
+        WasmAllocation::try_from_ribosome_encoding(encoded_value)
+    }
+}
+
+/// the bits as wasm sees them, reinterpreted as RibosomeEncodingBits before decoding
+impl TryFrom<RibosomeRuntimeBits> for WasmAllocation {
+    type Error = AllocationError;
+    fn try_from(runtime_value: RibosomeRuntimeBits) -> Result<Self, Self::Error> {
+        WasmAllocation::try_from(runtime_value as RibosomeEncodingBits)
+    }
+}
+
 impl WasmAllocation {
-    /// equivalent to TryFrom<RibosomeEncodingBits> for WasmAllocation
-    /// not implemented as a trait because RibosomeEncodingBits is a primitive and that would couple
-    /// allocations to ribosome encoding
+    /// same as TryFrom<RibosomeEncodingBits> for WasmAllocation
+    /// Success is ZeroLength and any Failure is OutOfBounds as neither is an allocation
     pub fn try_from_ribosome_encoding(encoded_value: RibosomeEncodingBits) -> AllocationResult {
         match RibosomeEncodedValue::from(encoded_value) {
             RibosomeEncodedValue::Success => Err(AllocationError::ZeroLength),
@@ -81,6 +122,36 @@ impl WasmAllocation {
     pub fn as_ribosome_encoding(&self) -> RibosomeEncodingBits {
         RibosomeEncodedValue::from(self.clone()).into()
     }
+
+    /// same as From<WasmAllocation> for RibosomeEncodedAllocation
+    /// the offset is merged into the high bits and the length into the low bits
+    /// ```
+    /// # extern crate holochain_wasm_utils;
+    /// # use holochain_wasm_utils::memory::allocation::{Length, Offset, WasmAllocation};
+    /// let allocation = WasmAllocation::new(Offset::from(4), Length::from(8)).unwrap();
+    /// let encoded = allocation.as_ribosome_encoded();
+    /// assert_eq!(4, encoded.offset());
+    /// assert_eq!(8, encoded.length());
+    /// ```
+    pub fn as_ribosome_encoded(self) -> RibosomeEncodedAllocation {
+        RibosomeEncodedAllocation::from(self)
+    }
+
+    /// same as TryFrom<RibosomeEncodedAllocation> for WasmAllocation, the inverse of
+    /// as_ribosome_encoded()
+    /// the encoding is unchecked so the same errors as WasmAllocation::new are possible
+    /// ```
+    /// # extern crate holochain_wasm_utils;
+    /// # use holochain_wasm_utils::memory::allocation::{Length, Offset, WasmAllocation};
+    /// let allocation = WasmAllocation::new(Offset::from(4), Length::from(8)).unwrap();
+    /// assert_eq!(
+    ///     Ok(allocation),
+    ///     WasmAllocation::try_from_ribosome_encoded(allocation.as_ribosome_encoded()),
+    /// );
+    /// ```
+    pub fn try_from_ribosome_encoded(encoded: RibosomeEncodedAllocation) -> AllocationResult {
+        WasmAllocation::try_from(encoded)
+    }
 }
 
 impl WasmStack {
@@ -112,19 +183,77 @@ pub fn return_code_for_allocation_result(result: AllocationResult) -> RibosomeEn
     }
 }
 
+/// RibosomeEncodedValue::from_allocation_result(..) constructor syntax for host functions
+/// an inherent method isn't possible as RibosomeEncodedValue is defined in core_types
+/// errors map through From<AllocationError> for RibosomeErrorCode
+pub trait FromAllocationResult {
+    fn from_allocation_result(result: AllocationResult) -> Self;
+}
+
+impl FromAllocationResult for RibosomeEncodedValue {
+    fn from_allocation_result(result: AllocationResult) -> Self {
+        return_code_for_allocation_result(result)
+    }
+}
+
+/// RibosomeEncodedValue::into_result() for host code that wants ? rather than a three way match
+/// a trait for the same reason as FromAllocationResult
+/// - Success is Ok(None)
+/// - Allocation is Ok(Some(..)) when it decodes to a valid WasmAllocation
+/// - Failure and malformed allocations are Err
+pub trait IntoAllocationResult {
+    fn into_result(self) -> Result<Option<WasmAllocation>, RibosomeErrorCode>;
+}
+
+impl IntoAllocationResult for RibosomeEncodedValue {
+    fn into_result(self) -> Result<Option<WasmAllocation>, RibosomeErrorCode> {
+        match self {
+            RibosomeEncodedValue::Success => Ok(None),
+            RibosomeEncodedValue::Allocation(allocation) => WasmAllocation::try_from(allocation)
+                .map(Some)
+                .map_err(RibosomeErrorCode::from),
+            RibosomeEncodedValue::Failure(err_code) => Err(err_code),
+        }
+    }
+}
+
+/// RibosomeEncodedValue::allocation_as_wasm() for host code holding a return value by reference
+/// a trait for the same reason as FromAllocationResult
+/// same as TryFrom<RibosomeEncodedValue> for WasmAllocation so Failure codes are preserved
+pub trait AllocationAsWasm {
+    fn allocation_as_wasm(&self) -> Result<WasmAllocation, HolochainError>;
+}
+
+impl AllocationAsWasm for RibosomeEncodedValue {
+    fn allocation_as_wasm(&self) -> Result<WasmAllocation, HolochainError> {
+        WasmAllocation::try_from(self.clone())
+    }
+}
+
+/// decodes many raw return values in one pass e.g. collected from parallel zome calls
+/// returns (allocations, errors, count of plain successes)
+/// each value is decoded as by into_result() so allocations that fail to decode are errors
+pub fn decode_batch(
+    raw: &[RibosomeRuntimeBits],
+) -> (Vec<WasmAllocation>, Vec<RibosomeErrorCode>, usize) {
+    let mut allocations = Vec::new();
+    let mut errors = Vec::new();
+    let mut successes = 0;
+    for bits in raw {
+        match RibosomeEncodedValue::from(*bits as RibosomeEncodingBits).into_result() {
+            Ok(None) => successes += 1,
+            Ok(Some(allocation)) => allocations.push(allocation),
+            Err(error_code) => errors.push(error_code),
+        }
+    }
+    (allocations, errors, successes)
+}
+
 pub fn load_ribosome_encoded_string(
     encoded_value: RibosomeEncodingBits,
 ) -> Result<String, HolochainError> {
     // almost the same as WasmAllocation::try_from_ribosome_encoding but maps to HolochainError
-    match RibosomeEncodedValue::from(encoded_value) {
-        RibosomeEncodedValue::Success => Err(HolochainError::Ribosome(
-            RibosomeErrorCode::ZeroSizedAllocation,
-        ))?,
-        RibosomeEncodedValue::Failure(err_code) => Err(HolochainError::Ribosome(err_code))?,
-        RibosomeEncodedValue::Allocation(ribosome_allocation) => {
-            Ok(WasmAllocation::try_from(ribosome_allocation)?.read_to_string())
-        }
-    }
+    Ok(WasmAllocation::try_from(RibosomeEncodedValue::from(encoded_value))?.read_to_string())
 }
 
 pub fn load_ribosome_encoded_json<J: TryFrom<JsonString>>(
@@ -145,13 +274,16 @@ pub mod tests {
     use holochain_core_types::{
         bits_n_pieces::u64_merge_bits,
         error::{
-            RibosomeEncodedAllocation, RibosomeEncodedValue, RibosomeEncodingBits,
-            RibosomeErrorCode,
+            codes, HolochainError, IntoRibosomeErrorCode, RibosomeEncodedAllocation,
+            RibosomeEncodedValue, RibosomeEncodingBits, RibosomeErrorCode, RibosomeRuntimeBits,
         },
     };
     use memory::{
         allocation::{AllocationError, Length, Offset, WasmAllocation},
-        ribosome::return_code_for_allocation_result,
+        ribosome::{
+            decode_batch, return_code_for_allocation_result, AllocationAsWasm,
+            FromAllocationResult, IntoAllocationResult,
+        },
         stack::{Top, WasmStack},
     };
     use std::convert::TryFrom;
@@ -195,6 +327,32 @@ pub mod tests {
         );
     }
 
+    #[test]
+    fn as_ribosome_encoded_test() {
+        let allocation = WasmAllocation::new(Offset::from(4), Length::from(8)).unwrap();
+        assert_eq!(
+            RibosomeEncodedAllocation::from(u64_merge_bits(4, 8)),
+            allocation.as_ribosome_encoded(),
+        );
+        assert_eq!(
+            Ok(allocation),
+            WasmAllocation::try_from_ribosome_encoded(allocation.as_ribosome_encoded()),
+        );
+
+        assert_eq!(
+            Err(AllocationError::ZeroLength),
+            WasmAllocation::try_from_ribosome_encoded(RibosomeEncodedAllocation::from(
+                u64_merge_bits(4, 0)
+            )),
+        );
+        assert_eq!(
+            Err(AllocationError::OutOfBounds),
+            WasmAllocation::try_from_ribosome_encoded(RibosomeEncodedAllocation::from(
+                u64_merge_bits(std::u32::MAX, 1)
+            )),
+        );
+    }
+
     #[test]
     fn ribosome_encoded_value_from_allocation_test() {
         assert_eq!(
@@ -208,6 +366,86 @@ pub mod tests {
         );
     }
 
+    #[test]
+    /// the core_types constructor can't call WasmAllocation::new so must agree with it
+    fn ribosome_encoded_value_allocation_agrees_with_new_test() {
+        for (offset, length) in vec![
+            (0, 0),
+            (4, 0),
+            (0, 1),
+            (4, 8),
+            (std::u32::MAX - 1, 1),
+            (std::u32::MAX, 1),
+            (std::u32::MAX, std::u32::MAX),
+        ] {
+            assert_eq!(
+                WasmAllocation::new(Offset::from(offset), Length::from(length))
+                    .map(RibosomeEncodedValue::from)
+                    .map_err(RibosomeErrorCode::from),
+                RibosomeEncodedValue::allocation(offset, length),
+            );
+        }
+    }
+
+    #[test]
+    fn try_allocation_from_ribosome_encoded_value_test() {
+        assert_eq!(
+            Err(HolochainError::Ribosome(
+                RibosomeErrorCode::ZeroSizedAllocation
+            )),
+            WasmAllocation::try_from(RibosomeEncodedValue::Success),
+        );
+
+        assert_eq!(
+            Err(HolochainError::Ribosome(RibosomeErrorCode::OutOfMemory)),
+            WasmAllocation::try_from(RibosomeEncodedValue::Failure(
+                RibosomeErrorCode::OutOfMemory
+            )),
+        );
+
+        assert_eq!(
+            Err(HolochainError::from(AllocationError::OutOfBounds)),
+            WasmAllocation::try_from(RibosomeEncodedValue::Allocation(
+                RibosomeEncodedAllocation::from(u64_merge_bits(std::u32::MAX, std::u32::MAX))
+            )),
+        );
+
+        let allocation = WasmAllocation {
+            offset: Offset::from(4),
+            length: Length::from(8),
+        };
+        assert_eq!(
+            Ok(allocation),
+            WasmAllocation::try_from(RibosomeEncodedValue::from(allocation)),
+        );
+    }
+
+    #[test]
+    fn allocation_as_wasm_test() {
+        let allocation = WasmAllocation {
+            offset: Offset::from(4),
+            length: Length::from(8),
+        };
+        assert_eq!(
+            Ok(allocation),
+            RibosomeEncodedValue::from(allocation).allocation_as_wasm(),
+        );
+
+        for value in vec![
+            RibosomeEncodedValue::Success,
+            RibosomeEncodedValue::Failure(RibosomeErrorCode::OutOfMemory),
+            RibosomeEncodedValue::Allocation(RibosomeEncodedAllocation::from(u64_merge_bits(
+                std::u32::MAX,
+                std::u32::MAX,
+            ))),
+        ] {
+            assert_eq!(
+                WasmAllocation::try_from(value.clone()),
+                value.allocation_as_wasm(),
+            );
+        }
+    }
+
     #[test]
     fn ribosome_error_from_allocation_error_test() {
         assert_eq!(
@@ -229,6 +467,26 @@ pub mod tests {
             RibosomeErrorCode::NotAnAllocation,
             RibosomeErrorCode::from(AllocationError::Serialization),
         );
+
+        assert_eq!(
+            RibosomeErrorCode::OutOfMemory,
+            RibosomeErrorCode::from(AllocationError::StackOverflow {
+                requested: 2,
+                available: 1,
+            }),
+        );
+
+        assert_eq!(
+            RibosomeErrorCode::NotAnAllocation,
+            RibosomeErrorCode::from(AllocationError::ReservedRegion),
+        );
+
+        assert_eq!(
+            RibosomeErrorCode::Unspecified,
+            RibosomeErrorCode::from(AllocationError::Corrupt {
+                reason: "missing magic bytes".into(),
+            }),
+        );
     }
 
     #[test]
@@ -254,6 +512,77 @@ pub mod tests {
         );
     }
 
+    #[test]
+    fn allocation_error_ribosome_code_test() {
+        for allocation_error in vec![
+            AllocationError::OutOfBounds,
+            AllocationError::ZeroLength,
+            AllocationError::BadStackAlignment,
+            AllocationError::Serialization,
+            AllocationError::StackOverflow {
+                requested: 2,
+                available: 1,
+            },
+            AllocationError::ReservedRegion,
+            AllocationError::Corrupt {
+                reason: "missing magic bytes".into(),
+            },
+        ] {
+            assert_eq!(
+                RibosomeErrorCode::from(allocation_error.clone()),
+                allocation_error.ribosome_code(),
+            );
+        }
+    }
+
+    #[test]
+    fn try_allocation_from_ribosome_bits_test() {
+        for (bits, expected) in vec![
+            (0, Err(AllocationError::ZeroLength)),
+            (
+                RibosomeEncodingBits::from(RibosomeEncodedValue::Failure(
+                    RibosomeErrorCode::OutOfMemory,
+                )),
+                Err(AllocationError::OutOfBounds),
+            ),
+            (
+                u64_merge_bits(std::u32::MAX, 0),
+                Err(AllocationError::OutOfBounds),
+            ),
+            (
+                u64_merge_bits(std::u32::MAX, std::u32::MAX),
+                Err(AllocationError::OutOfBounds),
+            ),
+            (
+                u64_merge_bits(std::u32::MAX, 1),
+                Err(AllocationError::OutOfBounds),
+            ),
+            (
+                u64_merge_bits(0, 1),
+                WasmAllocation::new(Offset::from(0), Length::from(1)),
+            ),
+            (
+                u64_merge_bits(4, 8),
+                WasmAllocation::new(Offset::from(4), Length::from(8)),
+            ),
+            (
+                u64_merge_bits(std::u32::MAX - 1, 1),
+                WasmAllocation::new(Offset::from(std::u32::MAX - 1), Length::from(1)),
+            ),
+            (
+                u64_merge_bits(0, std::u32::MAX),
+                WasmAllocation::new(Offset::from(0), Length::from(std::u32::MAX)),
+            ),
+        ] {
+            assert_eq!(expected, WasmAllocation::try_from(bits));
+            assert_eq!(
+                expected,
+                WasmAllocation::try_from(bits as RibosomeRuntimeBits),
+            );
+            assert_eq!(expected, WasmAllocation::try_from_ribosome_encoding(bits));
+        }
+    }
+
     #[test]
     fn ribosome_encoding_test() {
         assert_eq!(
@@ -280,6 +609,18 @@ pub mod tests {
             )),
             AllocationError::Serialization.as_ribosome_encoding(),
         );
+        assert_eq!(
+            codes::OUT_OF_MEMORY,
+            AllocationError::StackOverflow {
+                requested: 2,
+                available: 1,
+            }
+            .as_ribosome_encoding(),
+        );
+        assert_eq!(
+            codes::NOT_AN_ALLOCATION,
+            AllocationError::ReservedRegion.as_ribosome_encoding(),
+        );
     }
 
     #[test]
@@ -295,7 +636,10 @@ pub mod tests {
         );
 
         assert_eq!(
-            Ok(WasmStack { top: Top(4) }),
+            Ok(WasmStack {
+                top: Top(4),
+                ceiling: None,
+            }),
             // 2 + 2 = 4
             WasmStack::try_from_ribosome_encoding(
                 0b00000000000000000000000000000010_00000000000000000000000000000010
@@ -331,4 +675,109 @@ pub mod tests {
         );
     }
 
+    #[test]
+    fn from_allocation_result_test() {
+        let allocation = WasmAllocation::new(Offset::from(5), Length::from(5)).unwrap();
+        assert_eq!(
+            RibosomeEncodedValue::Allocation(RibosomeEncodedAllocation::from(allocation)),
+            RibosomeEncodedValue::from_allocation_result(Ok(allocation)),
+        );
+
+        assert_eq!(
+            RibosomeEncodedValue::Failure(RibosomeErrorCode::from(AllocationError::OutOfBounds)),
+            RibosomeEncodedValue::from_allocation_result(WasmAllocation::new(
+                Offset::from(std::u32::MAX),
+                Length::from(1)
+            )),
+        );
+
+        assert_eq!(
+            RibosomeEncodedValue::Failure(RibosomeErrorCode::ZeroSizedAllocation),
+            RibosomeEncodedValue::from_allocation_result(WasmAllocation::new(
+                Offset::from(1),
+                Length::from(0)
+            )),
+        );
+    }
+
+    #[test]
+    fn into_result_test() {
+        assert_eq!(Ok(None), RibosomeEncodedValue::Success.into_result());
+
+        let allocation = WasmAllocation::new(Offset::from(5), Length::from(5)).unwrap();
+        assert_eq!(
+            Ok(Some(allocation)),
+            RibosomeEncodedValue::from(allocation).into_result(),
+        );
+
+        assert_eq!(
+            Err(RibosomeErrorCode::CallbackFailed),
+            RibosomeEncodedValue::Failure(RibosomeErrorCode::CallbackFailed).into_result(),
+        );
+
+        // extends beyond the max memory
+        assert_eq!(
+            Err(RibosomeErrorCode::OutOfMemory),
+            RibosomeEncodedValue::Allocation(RibosomeEncodedAllocation::from(u64_merge_bits(
+                std::u32::MAX,
+                1
+            )))
+            .into_result(),
+        );
+    }
+
+    #[test]
+    /// a single typed value carried end to end through the raw encoding
+    fn encoded_value_bits_round_trip_test() {
+        let allocation = WasmAllocation::new(Offset::from(12), Length::from(34)).unwrap();
+        for (value, decoded) in vec![
+            (RibosomeEncodedValue::Success, Ok(None)),
+            (RibosomeEncodedValue::from(allocation), Ok(Some(allocation))),
+            (
+                RibosomeEncodedValue::Failure(RibosomeErrorCode::IoError),
+                Err(RibosomeErrorCode::IoError),
+            ),
+        ] {
+            let bits = RibosomeEncodingBits::from(value.clone());
+            assert_eq!(value, RibosomeEncodedValue::from(bits));
+            assert_eq!(decoded, RibosomeEncodedValue::from(bits).into_result());
+        }
+    }
+
+    #[test]
+    fn decode_batch_test() {
+        let first = WasmAllocation::new(Offset::from(1), Length::from(2)).unwrap();
+        let second = WasmAllocation::new(Offset::from(3), Length::from(4)).unwrap();
+        let raw: Vec<RibosomeRuntimeBits> = vec![
+            RibosomeEncodedValue::Success,
+            RibosomeEncodedValue::from(first),
+            RibosomeEncodedValue::Failure(RibosomeErrorCode::CallbackFailed),
+            RibosomeEncodedValue::Success,
+            // an allocation that extends beyond the max memory
+            RibosomeEncodedValue::Allocation(RibosomeEncodedAllocation::from(u64_merge_bits(
+                std::u32::MAX,
+                std::u32::MAX,
+            ))),
+            RibosomeEncodedValue::from(second),
+            RibosomeEncodedValue::Success,
+        ]
+        .into_iter()
+        .map(|value| RibosomeEncodingBits::from(value) as RibosomeRuntimeBits)
+        .collect();
+
+        assert_eq!(
+            (
+                vec![first, second],
+                vec![
+                    RibosomeErrorCode::CallbackFailed,
+                    RibosomeErrorCode::OutOfMemory
+                ],
+                3,
+            ),
+            decode_batch(&raw),
+        );
+
+        assert_eq!((vec![], vec![], 0), decode_batch(&[]));
+    }
+
 }
@@ -1,10 +1,11 @@
+use holochain_core_types::{error::HolochainError, json::JsonString};
 use memory::{
     allocation::{AllocationError, Length, WasmAllocation},
     MemoryBits, MemoryInt, MEMORY_INT_MAX,
 };
 use std::convert::TryFrom;
 
-#[derive(Copy, Clone, Default, Debug, PartialEq)]
+#[derive(Copy, Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
 // pub in crate for testing
 pub struct Top(pub(in crate::memory) MemoryInt);
 
@@ -26,20 +27,24 @@ impl From<Top> for MemoryBits {
     }
 }
 
-#[derive(Copy, Clone, Default, Debug, PartialEq)]
+/// serializes as {"top": N, "ceiling": N|null} for snapshot/resume
+/// the stack only tracks its top so there is no list of allocations to checkpoint
+#[derive(Copy, Clone, Default, Debug, PartialEq, Serialize, Deserialize, DefaultJson)]
 pub struct WasmStack {
     // pub in crate for testing
     pub(in crate::memory) top: Top,
+    /// optional budget below the max that allocations can't extend past
+    pub(in crate::memory) ceiling: Option<MemoryInt>,
 }
 
 impl WasmStack {
     // represent the max as MemoryBits type to allow gt comparisons
-    pub fn max() -> MemoryBits {
+    pub const fn max() -> MemoryBits {
         MEMORY_INT_MAX
     }
 
     // min compares lt so can be a MemoryInt
-    pub fn min() -> MemoryInt {
+    pub const fn min() -> MemoryInt {
         0
     }
 
@@ -47,6 +52,32 @@ impl WasmStack {
     pub fn new() -> WasmStack {
         WasmStack {
             top: Top(WasmStack::min()),
+            ceiling: None,
+        }
+    }
+
+    /// a new stack that rejects allocations ending past ceiling with OutOfBounds
+    /// e.g. to enforce a per instance memory budget on constrained hosts
+    pub fn with_ceiling(ceiling: MemoryInt) -> WasmStack {
+        WasmStack {
+            ceiling: Some(ceiling),
+            ..WasmStack::new()
+        }
+    }
+
+    /// restores a stack previously checkpointed by serializing it to JsonString
+    /// fails if the snapshot is malformed or its top is already past its ceiling
+    pub fn from_snapshot(json: JsonString) -> Result<WasmStack, HolochainError> {
+        let stack = WasmStack::try_from(json)?;
+        match stack.ceiling {
+            Some(ceiling) if MemoryInt::from(stack.top) > ceiling => {
+                Err(HolochainError::SerializationError(format!(
+                    "stack snapshot top {} is past ceiling {}",
+                    MemoryInt::from(stack.top),
+                    ceiling
+                )))
+            }
+            _ => Ok(stack),
         }
     }
 
@@ -57,23 +88,38 @@ impl WasmStack {
     pub fn allocate(&mut self, allocation: WasmAllocation) -> Result<Top, AllocationError> {
         if MemoryInt::from(self.top()) != MemoryInt::from(allocation.offset()) {
             Err(AllocationError::BadStackAlignment)
-        } else if MemoryBits::from(self.top()) + MemoryBits::from(allocation.length())
-            > WasmStack::max()
-        {
-            Err(AllocationError::OutOfBounds)
         } else {
             // @todo i don't know why we return the old top instead of new one?
+            let new_top = allocation.offset().try_advance(allocation.length())?;
+            match self.ceiling {
+                Some(ceiling) if MemoryInt::from(new_top) > ceiling => {
+                    return Err(AllocationError::OutOfBounds);
+                }
+                _ => (),
+            }
             let old_top = self.top;
-            self.top =
-                Top(MemoryInt::from(allocation.offset()) + MemoryInt::from(allocation.length()));
+            self.top = Top(new_top.into());
             Ok(old_top)
         }
     }
 
+    /// allocates the next length bytes on the stack unless length is zero
+    /// an empty payload is Ok(None) and leaves the stack untouched
+    pub fn allocate_or_empty(
+        &mut self,
+        length: Length,
+    ) -> Result<Option<WasmAllocation>, AllocationError> {
+        match WasmAllocation::new_or_empty(MemoryInt::from(self.top()).into(), length)? {
+            Some(allocation) => {
+                self.allocate(allocation)?;
+                Ok(Some(allocation))
+            }
+            None => Ok(None),
+        }
+    }
+
     pub fn deallocate(&mut self, allocation: WasmAllocation) -> Result<Top, AllocationError> {
-        if MemoryInt::from(self.top())
-            != MemoryInt::from(allocation.offset()) + MemoryInt::from(allocation.length())
-        {
+        if MemoryInt::from(self.top()) != MemoryInt::from(allocation.end_offset()) {
             Err(AllocationError::BadStackAlignment)
         } else if MemoryInt::from(allocation.offset()) < WasmStack::min() {
             Err(AllocationError::OutOfBounds)
@@ -88,6 +134,10 @@ impl WasmStack {
     pub fn top(self) -> Top {
         self.top
     }
+
+    pub fn ceiling(self) -> Option<MemoryInt> {
+        self.ceiling
+    }
 }
 
 impl TryFrom<WasmAllocation> for WasmStack {
@@ -95,6 +145,7 @@ impl TryFrom<WasmAllocation> for WasmStack {
     fn try_from(allocation: WasmAllocation) -> Result<Self, Self::Error> {
         let mut stack = WasmStack {
             top: Top(allocation.offset().into()),
+            ceiling: None,
         };
         stack.allocate(allocation)?;
         Ok(stack)
@@ -104,7 +155,7 @@ impl TryFrom<WasmAllocation> for WasmStack {
 #[cfg(test)]
 pub mod memory_tests {
 
-    use holochain_core_types::bits_n_pieces::U16_MAX;
+    use holochain_core_types::{bits_n_pieces::U16_MAX, error::HolochainError, json::JsonString};
     use memory::{
         allocation::{AllocationError, Length, Offset, WasmAllocation},
         stack::{Top, WasmStack},
@@ -143,7 +194,13 @@ pub mod memory_tests {
 
     #[test]
     fn stack_new_test() {
-        assert_eq!(WasmStack { top: Top(0) }, WasmStack::new(),);
+        assert_eq!(
+            WasmStack {
+                top: Top(0),
+                ceiling: None,
+            },
+            WasmStack::new(),
+        );
     }
 
     #[test]
@@ -214,9 +271,65 @@ pub mod memory_tests {
         assert_eq!(stack.top(), Top(U16_MAX + 13),);
     }
 
+    #[test]
+    fn with_ceiling_test() {
+        let stack = WasmStack::with_ceiling(10);
+        assert_eq!(Top(0), stack.top());
+        assert_eq!(Some(10), stack.ceiling());
+        assert_eq!(None, WasmStack::new().ceiling());
+
+        // just below the ceiling
+        let mut stack = WasmStack::with_ceiling(10);
+        let allocation = stack.next_allocation(Length::from(9)).unwrap();
+        assert_eq!(Ok(Top(0)), stack.allocate(allocation));
+        assert_eq!(Top(9), stack.top());
+
+        // at the ceiling
+        let allocation = stack.next_allocation(Length::from(1)).unwrap();
+        assert_eq!(Ok(Top(9)), stack.allocate(allocation));
+        assert_eq!(Top(10), stack.top());
+
+        // just above the ceiling
+        let mut stack = WasmStack::with_ceiling(10);
+        let allocation = stack.next_allocation(Length::from(11)).unwrap();
+        assert_eq!(
+            Err(AllocationError::OutOfBounds),
+            stack.allocate(allocation)
+        );
+        assert_eq!(Top(0), stack.top());
+    }
+
+    #[test]
+    fn allocate_or_empty_test() {
+        let mut stack = WasmStack::new();
+
+        assert_eq!(Ok(None), stack.allocate_or_empty(Length::from(0)),);
+        assert_eq!(stack.top(), Top(0),);
+
+        assert_eq!(
+            Ok(Some(
+                WasmAllocation::new(Offset::from(0), Length::from(5)).unwrap()
+            )),
+            stack.allocate_or_empty(Length::from(5)),
+        );
+        assert_eq!(stack.top(), Top(5),);
+
+        assert_eq!(Ok(None), stack.allocate_or_empty(Length::from(0)),);
+        assert_eq!(stack.top(), Top(5),);
+
+        assert_eq!(
+            Err(AllocationError::OutOfBounds),
+            stack.allocate_or_empty(Length::from(std::u32::MAX)),
+        );
+        assert_eq!(stack.top(), Top(5),);
+    }
+
     #[test]
     fn deallocate_test() {
-        let mut stack = WasmStack { top: Top(50) };
+        let mut stack = WasmStack {
+            top: Top(50),
+            ceiling: None,
+        };
         let unaligned_allocation = WasmAllocation::new(Offset::from(50), Length::from(5)).unwrap();
         assert_eq!(
             Err(AllocationError::BadStackAlignment),
@@ -233,7 +346,7 @@ pub mod memory_tests {
     #[test]
     fn top_test() {
         let top = Top(123);
-        let stack = WasmStack { top };
+        let stack = WasmStack { top, ceiling: None };
         assert_eq!(top, stack.top(),);
     }
 
@@ -250,7 +363,10 @@ pub mod memory_tests {
         );
 
         assert_eq!(
-            Ok(WasmStack { top: Top(60) }),
+            Ok(WasmStack {
+                top: Top(60),
+                ceiling: None,
+            }),
             WasmStack::try_from(WasmAllocation {
                 offset: Offset::from(30),
                 length: Length::from(30)
@@ -259,7 +375,10 @@ pub mod memory_tests {
 
         let big = U16_MAX * 3;
         assert_eq!(
-            Ok(WasmStack { top: Top(big * 2) }),
+            Ok(WasmStack {
+                top: Top(big * 2),
+                ceiling: None,
+            }),
             WasmStack::try_from(WasmAllocation {
                 offset: Offset::from(big),
                 length: Length::from(big),
@@ -267,4 +386,58 @@ pub mod memory_tests {
         );
     }
 
+    #[test]
+    fn stack_snapshot_round_trip_test() {
+        let mut stack = WasmStack::new();
+        assert_eq!(
+            JsonString::from("{\"top\":0,\"ceiling\":null}"),
+            JsonString::from(stack),
+        );
+
+        let first = stack.next_allocation(Length::from(5)).unwrap();
+        stack.allocate(first).unwrap();
+        let second = stack.next_allocation(Length::from(8)).unwrap();
+        stack.allocate(second).unwrap();
+        assert_eq!(
+            JsonString::from("{\"top\":13,\"ceiling\":null}"),
+            JsonString::from(stack),
+        );
+
+        let mut restored = WasmStack::from_snapshot(JsonString::from(stack)).unwrap();
+        assert_eq!(stack, restored);
+
+        // the restored stack carries on where the original left off
+        assert_eq!(Ok(Top(13)), restored.deallocate(second));
+        assert_eq!(Top(5), restored.top());
+        assert_eq!(
+            restored,
+            WasmStack::from_snapshot(JsonString::from(restored)).unwrap(),
+        );
+
+        let ceilinged = WasmStack::with_ceiling(10);
+        assert_eq!(
+            JsonString::from("{\"top\":0,\"ceiling\":10}"),
+            JsonString::from(ceilinged),
+        );
+        assert_eq!(
+            Ok(ceilinged),
+            WasmStack::from_snapshot(JsonString::from(ceilinged)),
+        );
+    }
+
+    #[test]
+    fn stack_from_snapshot_err_test() {
+        assert!(
+            WasmStack::from_snapshot(JsonString::from("{\"top\":\"foo\"}"))
+                .unwrap_err()
+                .is_serialization_error()
+        );
+
+        assert_eq!(
+            Err(HolochainError::SerializationError(
+                "stack snapshot top 11 is past ceiling 10".to_string()
+            )),
+            WasmStack::from_snapshot(JsonString::from("{\"top\":11,\"ceiling\":10}")),
+        );
+    }
 }
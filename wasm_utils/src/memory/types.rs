@@ -0,0 +1,38 @@
+//! Integer types shared by everything that reads, writes or encodes wasm memory.
+
+use holochain_core_types::bits_n_pieces::U32_MAX;
+
+/// offsets, lengths, etc.
+/// wasm32 linear memory is addressed with 32 bits so every offset or length fits a u32
+pub type MemoryInt = u32;
+
+/// encodes allocations as 2x MemoryInt in high/low bits etc.
+/// must be 2x larger than MemoryInt
+/// - offset + length of two MemoryInt values can't overflow so bounds can be checked with a
+///   simple gt comparison against MEMORY_INT_MAX
+/// - an allocation is encoded with the offset in the high bits and the length in the low bits
+///   @see holochain_core_types::bits_n_pieces::u64_merge_bits
+///   this is the same width as RibosomeEncodingBits so allocations can be returned to/from wasm
+pub type MemoryBits = u64;
+
+/// represents the max MemoryInt in MemoryBits to facilitate gt comparisons
+/// i.e. the highest offset any allocation may end at
+pub const MEMORY_INT_MAX: MemoryBits = U32_MAX;
+
+#[cfg(test)]
+pub mod tests {
+
+    use memory::types::{MemoryBits, MemoryInt, MEMORY_INT_MAX};
+    use std::mem::size_of;
+
+    #[test]
+    fn memory_bits_size_test() {
+        assert_eq!(size_of::<MemoryInt>() * 2, size_of::<MemoryBits>());
+    }
+
+    #[test]
+    fn memory_int_max_test() {
+        assert_eq!(MemoryInt::max_value() as MemoryBits, MEMORY_INT_MAX);
+    }
+
+}
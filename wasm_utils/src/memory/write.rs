@@ -4,7 +4,7 @@ use crate::memory::{
     MemoryBits, MemoryInt,
 };
 use holochain_core_types::json::JsonString;
-use memory::allocation::{AllocationResult, Length};
+use memory::allocation::{json_allocation_length, AllocationResult, Length};
 use std::{convert::TryInto, os::raw::c_char, slice};
 
 impl WasmStack {
@@ -38,11 +38,41 @@ impl WasmStack {
             .try_into()
             .map_err(|_| AllocationError::Serialization)?;
 
-        let json_bytes = j.into_bytes();
-        let json_bytes_len = json_bytes.len() as MemoryInt;
-        if MemoryBits::from(json_bytes_len) > WasmStack::max() {
-            return Err(AllocationError::OutOfBounds);
+        let json_bytes_len = json_allocation_length(&j)?;
+        self.write_in_wasm_memory(&j.into_bytes(), json_bytes_len)
+    }
+}
+
+impl WasmAllocation {
+    /// writes the JSON string into the allocation of memory
+    /// the inverse of read_json_string for hosts writing to a memory slice
+    /// OutOfBounds if the allocation extends past the end of memory or the bytes of the JSON
+    /// don't exactly fill the allocation
+    pub fn write_json_string(
+        self,
+        memory: &mut [u8],
+        data: &JsonString,
+    ) -> Result<(), AllocationError> {
+        let bytes = String::from(data).into_bytes();
+        let start = MemoryInt::from(self.offset()) as usize;
+        let end = start + usize::from(self.length());
+        if bytes.len() != usize::from(self.length()) || end > memory.len() {
+            Err(AllocationError::OutOfBounds)
+        } else {
+            memory[start..end].copy_from_slice(&bytes);
+            Ok(())
+        }
+    }
+
+    /// overwrites the allocation of memory with zeros e.g. to wipe sensitive data
+    /// OutOfBounds if the allocation extends past the end of memory, as per read_slice
+    pub fn zero(self, memory: &mut [u8]) -> Result<(), AllocationError> {
+        self.read_slice(memory)?;
+        let start = MemoryInt::from(self.offset()) as usize;
+        let end = start + usize::from(self.length());
+        for byte in memory[start..end].iter_mut() {
+            *byte = 0;
         }
-        self.write_in_wasm_memory(&json_bytes, Length::from(json_bytes_len))
+        Ok(())
     }
 }